@@ -0,0 +1,98 @@
+//! Measuring the encoding throughput in the actual environment.
+
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast};
+use web_sys::js_sys;
+
+use crate::{rs_encode, shards_len, RsError, RsErrorCode, RsShardsCollection};
+
+/// Measure encoding throughput for given parameters (in MB/s of original data).
+///
+/// Encodes pseudo-random original shards `iterations` times (at least once),
+/// after one (not measured) warm-up run. The time is measured with `performance.now()`,
+/// so the precision depends on the environment; use enough iterations
+/// for the measurement to take at least a few milliseconds.
+#[wasm_bindgen]
+pub fn benchmark(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    iterations: u32,
+) -> Result<f64, RsError> {
+    let performance = performance()?;
+    let originals = random_originals(original_count as usize, shard_bytes)?;
+    let iterations = iterations.max(1);
+
+    rs_encode(recovery_count as usize, shard_bytes as usize, &originals)?;
+
+    let start = performance.now();
+    for _ in 0..iterations {
+        rs_encode(recovery_count as usize, shard_bytes as usize, &originals)?;
+    }
+    // the timer may be too coarse to notice a very short run
+    let elapsed_ms = (performance.now() - start).max(0.001);
+
+    let megabytes = originals.data.len() as f64 * iterations as f64 / 1_000_000.0;
+    Ok(megabytes / (elapsed_ms / 1000.0))
+}
+
+/// `performance` object of the global scope (window, worker or node).
+pub(crate) fn performance() -> Result<web_sys::Performance, RsError> {
+    js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+        .ok()
+        .and_then(|performance| performance.dyn_into().ok())
+        .ok_or_else(|| {
+            RsError::new(
+                RsErrorCode::Unsupported,
+                "performance.now() is not available in this environment",
+            )
+        })
+}
+
+/// Original shards filled with pseudo-random bytes (xorshift).
+fn random_originals(
+    original_count: usize,
+    shard_bytes: u32,
+) -> Result<RsShardsCollection, RsError> {
+    let len = shards_len(
+        original_count,
+        shard_bytes as usize,
+        RsErrorCode::InvalidShardSize,
+    )?;
+    let mut state = 0x2545_f491_u32;
+    let data = (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+
+    Ok(RsShardsCollection {
+        length: original_count,
+        shard_len: shard_bytes,
+        data,
+        indices: None,
+    })
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn should_measure_positive_throughput() {
+        let throughput = benchmark(8, 4, 64, 10).unwrap();
+
+        assert!(throughput.is_finite());
+        assert!(throughput > 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_report_invalid_parameters() {
+        let err = benchmark(8, 4, 63, 10).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::InvalidShardSize);
+    }
+}
@@ -0,0 +1,191 @@
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys;
+
+use crate::limits::MAX_SHARDS;
+use crate::{RsError, RsErrorCode, RsShardsCollection, ShardsCollection};
+
+/// Assembles a [`ShardsCollection`] shard by shard.
+///
+/// Shards are copied into WASM memory as they are pushed.
+/// Shards added with [`ShardsCollectionBuilder::push`] get the index of their
+/// position, use [`ShardsCollectionBuilder::push_at`] to specify it explicitly.
+///
+/// NOTE the object holds WASM memory, so make sure to either call `build()`
+/// or `free()` on the JS side once it's not needed anymore.
+#[wasm_bindgen]
+pub struct ShardsCollectionBuilder {
+    shard_len: u32,
+    data: Vec<u8>,
+    indices: Vec<u16>,
+    explicit_indices: bool,
+}
+
+#[wasm_bindgen]
+impl ShardsCollectionBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(shard_len: u32) -> ShardsCollectionBuilder {
+        Self {
+            shard_len,
+            data: vec![],
+            indices: vec![],
+            explicit_indices: false,
+        }
+    }
+
+    /// Append a shard with the index of its position.
+    ///
+    /// At most `max_shards()` shards can be pushed (with either method).
+    #[wasm_bindgen]
+    pub fn push(&mut self, bytes: js_sys::Uint8Array) -> Result<(), RsError> {
+        self.check_capacity()?;
+        // can't truncate, there are less than `MAX_SHARDS` shards
+        let index = self.indices.len() as u16;
+        self.push_shard(index, &bytes.to_vec())
+    }
+
+    /// Append a shard with given shard `index`.
+    #[wasm_bindgen]
+    pub fn push_at(&mut self, index: u16, bytes: js_sys::Uint8Array) -> Result<(), RsError> {
+        self.explicit_indices = true;
+        self.push_shard(index, &bytes.to_vec())
+    }
+
+    /// Number of shards pushed so far.
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Whether no shards were pushed yet.
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Create the collection out of the pushed shards.
+    ///
+    /// The collection carries indices only if any of the shards was added with `push_at`.
+    #[wasm_bindgen]
+    pub fn build(self) -> ShardsCollection {
+        self.build_collection().into()
+    }
+}
+
+impl ShardsCollectionBuilder {
+    /// Make sure there is room for one more shard (indices are `u16`).
+    fn check_capacity(&self) -> Result<(), RsError> {
+        if self.indices.len() >= MAX_SHARDS {
+            return Err(RsError::new(
+                RsErrorCode::UnsupportedShardCount,
+                format!(
+                    "Cannot push more than {} shards into a collection.",
+                    MAX_SHARDS
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn push_shard(&mut self, index: u16, bytes: &[u8]) -> Result<(), RsError> {
+        self.check_capacity()?;
+        if bytes.len() != self.shard_len as usize {
+            return Err(RsError::new(
+                RsErrorCode::DifferentShardSize,
+                format!(
+                    "shard length ({}) does not match shard_len ({})",
+                    bytes.len(),
+                    self.shard_len
+                ),
+            ));
+        }
+
+        self.data.extend(bytes);
+        self.indices.push(index);
+        Ok(())
+    }
+
+    fn build_collection(self) -> RsShardsCollection {
+        RsShardsCollection {
+            length: self.indices.len(),
+            shard_len: self.shard_len,
+            data: self.data,
+            indices: self.explicit_indices.then_some(self.indices),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rs_decode_full, rs_encode};
+
+    const SHARD: usize = 64;
+
+    #[test]
+    fn should_build_collection_and_decode_it() {
+        let originals = RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data: (0..4 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect(),
+            indices: None,
+        };
+        let recovery = rs_encode(2, SHARD, &originals).unwrap();
+
+        let mut builder = ShardsCollectionBuilder::new(SHARD as u32);
+        builder.push_shard(0, originals.chunk_at(0)).unwrap();
+        builder.push_shard(5, recovery.chunk_at(1)).unwrap();
+        builder.push_shard(2, originals.chunk_at(2)).unwrap();
+        builder.push_shard(4, recovery.chunk_at(0)).unwrap();
+        builder.explicit_indices = true;
+        assert_eq!(builder.len(), 4);
+
+        let collection = builder.build_collection();
+        assert_eq!(collection.indices, Some(vec![0, 5, 2, 4]));
+
+        let decoded = rs_decode_full(4, 2, SHARD, collection).unwrap();
+        assert_eq!(decoded.data, originals.data);
+    }
+
+    #[test]
+    fn should_use_implicit_indices_by_default() {
+        let mut builder = ShardsCollectionBuilder::new(SHARD as u32);
+        builder.push_shard(0, &[1; SHARD]).unwrap();
+        builder.push_shard(1, &[2; SHARD]).unwrap();
+
+        let collection = builder.build_collection();
+
+        assert_eq!(collection.length, 2);
+        assert_eq!(collection.indices, None);
+    }
+
+    #[test]
+    fn should_reject_shard_of_different_length() {
+        let mut builder = ShardsCollectionBuilder::new(SHARD as u32);
+
+        let err = builder.push_shard(0, &[1; SHARD - 2]).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::DifferentShardSize);
+        assert!(builder.is_empty());
+    }
+
+    #[test]
+    fn should_reject_shards_beyond_max_shards() {
+        let mut builder = ShardsCollectionBuilder::new(2);
+        for i in 0..MAX_SHARDS {
+            builder.push_shard(i as u16, &[1, 2]).unwrap();
+        }
+        assert_eq!(
+            builder.check_capacity().unwrap_err().code(),
+            RsErrorCode::UnsupportedShardCount
+        );
+
+        // index `0` would be a duplicate of the first (implicit) one
+        let err = builder.push_shard(0, &[1, 2]).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::UnsupportedShardCount);
+        assert_eq!(builder.len(), MAX_SHARDS);
+
+        let collection = builder.build_collection();
+        assert_eq!(collection.chunk_index_at(MAX_SHARDS - 1), u16::MAX);
+    }
+}
@@ -0,0 +1,380 @@
+use reed_solomon::{ReedSolomonDecoder, ReedSolomonEncoder};
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys;
+
+use crate::{
+    recovery_collection, restored_collection, RsError, RsShardsCollection, ShardsCollection,
+};
+
+/// A reusable encoder.
+///
+/// Keeps the working memory of the underlying encoder between calls,
+/// so it's well suited for encoding a stream of equally-sized frames.
+/// Original shards are added one by one and after [`Encoder::encode`]
+/// the encoder is ready for the next frame with the same parameters.
+/// Use [`Encoder::reset`] to change the parameters.
+///
+/// Added shards are copied straight from JS memory into a buffer owned by
+/// the encoder (re-used for every shard), so there is no allocation per shard.
+///
+/// NOTE the object holds WASM memory, so make sure to call `free()`
+/// on the JS side once it's not needed anymore.
+#[wasm_bindgen]
+pub struct Encoder {
+    encoder: ReedSolomonEncoder,
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: u32,
+    /// Buffer the added shards are copied to from JS memory.
+    scratch: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Encoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        original_count: u16,
+        recovery_count: u16,
+        shard_bytes: u32,
+    ) -> Result<Encoder, RsError> {
+        let encoder = ReedSolomonEncoder::new(
+            original_count as usize,
+            recovery_count as usize,
+            shard_bytes as usize,
+        )?;
+
+        Ok(Self {
+            encoder,
+            original_count: original_count as usize,
+            recovery_count: recovery_count as usize,
+            shard_bytes,
+            scratch: vec![],
+        })
+    }
+
+    /// Add next original shard.
+    ///
+    /// Shards must be added in order.
+    #[wasm_bindgen]
+    pub fn add_original_shard(&mut self, data: js_sys::Uint8Array) -> Result<(), RsError> {
+        self.with_copy(&data, |encoder, data| encoder.add_original(data))
+    }
+
+    /// Compute recovery shards of all original shards added so far.
+    ///
+    /// Afterwards the encoder is ready to accept shards of the next frame.
+    #[wasm_bindgen]
+    pub fn encode(&mut self) -> Result<ShardsCollection, RsError> {
+        Ok(self.encode_recovery()?.into())
+    }
+
+    /// Re-initialize the encoder with new parameters, re-using its memory.
+    #[wasm_bindgen]
+    pub fn reset(
+        &mut self,
+        original_count: u16,
+        recovery_count: u16,
+        shard_bytes: u32,
+    ) -> Result<(), RsError> {
+        self.encoder.reset(
+            original_count as usize,
+            recovery_count as usize,
+            shard_bytes as usize,
+        )?;
+        self.original_count = original_count as usize;
+        self.recovery_count = recovery_count as usize;
+        self.shard_bytes = shard_bytes;
+
+        Ok(())
+    }
+}
+
+impl Encoder {
+    /// Copy `data` from JS memory to the scratch buffer and pass it to `f`.
+    fn with_copy<T>(
+        &mut self,
+        data: &js_sys::Uint8Array,
+        f: impl FnOnce(&mut Self, &[u8]) -> T,
+    ) -> T {
+        let mut scratch = core::mem::take(&mut self.scratch);
+        scratch.resize(data.length() as usize, 0);
+        data.copy_to(&mut scratch);
+        let result = f(self, &scratch);
+        self.scratch = scratch;
+
+        result
+    }
+
+    fn add_original(&mut self, data: &[u8]) -> Result<(), RsError> {
+        Ok(self.encoder.add_original_shard(data)?)
+    }
+
+    fn encode_recovery(&mut self) -> Result<RsShardsCollection, RsError> {
+        let result = self.encoder.encode()?;
+
+        Ok(recovery_collection(
+            self.original_count,
+            self.recovery_count,
+            self.shard_bytes,
+            result.recovery_iter(),
+        ))
+    }
+}
+
+/// A reusable decoder.
+///
+/// Shards can be added one by one (in any order) as they arrive,
+/// without assembling a [`ShardsCollection`] up front.
+/// After [`Decoder::decode`] the decoder is ready for the next frame
+/// with the same parameters. Use [`Decoder::reset`] to change the parameters.
+///
+/// Added shards are copied straight from JS memory into a buffer owned by
+/// the decoder (re-used for every shard), so there is no allocation per shard.
+///
+/// NOTE the object holds WASM memory, so make sure to call `free()`
+/// on the JS side once it's not needed anymore.
+#[wasm_bindgen]
+pub struct Decoder {
+    decoder: ReedSolomonDecoder,
+    original_count: usize,
+    shard_bytes: u32,
+    /// Number of distinct shards added to the current frame.
+    received: usize,
+    /// Buffer the added shards are copied to from JS memory.
+    scratch: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Decoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        original_count: u16,
+        recovery_count: u16,
+        shard_bytes: u32,
+    ) -> Result<Decoder, RsError> {
+        let decoder = ReedSolomonDecoder::new(
+            original_count as usize,
+            recovery_count as usize,
+            shard_bytes as usize,
+        )?;
+
+        Ok(Self {
+            decoder,
+            original_count: original_count as usize,
+            shard_bytes,
+            received: 0,
+            scratch: vec![],
+        })
+    }
+
+    /// Add original shard with given `index`.
+    #[wasm_bindgen]
+    pub fn add_original_shard(
+        &mut self,
+        index: u16,
+        data: js_sys::Uint8Array,
+    ) -> Result<(), RsError> {
+        self.with_copy(&data, |decoder, data| {
+            decoder.add_original(index as usize, data)
+        })
+    }
+
+    /// Add recovery shard with given `index`.
+    ///
+    /// NOTE the index is relative, i.e. the first recovery shard has index `0`.
+    #[wasm_bindgen]
+    pub fn add_recovery_shard(
+        &mut self,
+        index: u16,
+        data: js_sys::Uint8Array,
+    ) -> Result<(), RsError> {
+        self.with_copy(&data, |decoder, data| {
+            decoder.add_recovery(index as usize, data)
+        })
+    }
+
+    /// Number of additional (distinct) shards required to decode the current frame.
+    #[wasm_bindgen(getter)]
+    pub fn remaining_needed(&self) -> usize {
+        self.original_count.saturating_sub(self.received)
+    }
+
+    /// Whether enough shards were added to decode the current frame.
+    #[wasm_bindgen(getter)]
+    pub fn ready(&self) -> bool {
+        self.remaining_needed() == 0
+    }
+
+    /// Restore missing original shards.
+    ///
+    /// The result contains only the restored original shards.
+    /// Afterwards the decoder is ready to accept shards of the next frame.
+    #[wasm_bindgen]
+    pub fn decode(&mut self) -> Result<ShardsCollection, RsError> {
+        Ok(self.decode_restored()?.into())
+    }
+
+    /// Re-initialize the decoder with new parameters, re-using its memory.
+    #[wasm_bindgen]
+    pub fn reset(
+        &mut self,
+        original_count: u16,
+        recovery_count: u16,
+        shard_bytes: u32,
+    ) -> Result<(), RsError> {
+        self.decoder.reset(
+            original_count as usize,
+            recovery_count as usize,
+            shard_bytes as usize,
+        )?;
+        self.original_count = original_count as usize;
+        self.shard_bytes = shard_bytes;
+        self.received = 0;
+
+        Ok(())
+    }
+}
+
+impl Decoder {
+    /// Copy `data` from JS memory to the scratch buffer and pass it to `f`.
+    fn with_copy<T>(
+        &mut self,
+        data: &js_sys::Uint8Array,
+        f: impl FnOnce(&mut Self, &[u8]) -> T,
+    ) -> T {
+        let mut scratch = core::mem::take(&mut self.scratch);
+        scratch.resize(data.length() as usize, 0);
+        data.copy_to(&mut scratch);
+        let result = f(self, &scratch);
+        self.scratch = scratch;
+
+        result
+    }
+
+    // NOTE the underlying decoder rejects duplicated shards,
+    // so only distinct shards are counted as received.
+    fn add_original(&mut self, index: usize, data: &[u8]) -> Result<(), RsError> {
+        self.decoder.add_original_shard(index, data)?;
+        self.received += 1;
+        Ok(())
+    }
+
+    fn add_recovery(&mut self, index: usize, data: &[u8]) -> Result<(), RsError> {
+        self.decoder.add_recovery_shard(index, data)?;
+        self.received += 1;
+        Ok(())
+    }
+
+    fn decode_restored(&mut self) -> Result<RsShardsCollection, RsError> {
+        let result = self.decoder.decode()?;
+        self.received = 0;
+
+        Ok(restored_collection(
+            self.shard_bytes,
+            result.restored_original_iter(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs_encode;
+
+    const SHARD: usize = 64;
+
+    fn frame(seed: u8) -> RsShardsCollection {
+        let mut data = vec![];
+        for i in 0..3 {
+            data.extend([seed.wrapping_add(i); SHARD]);
+        }
+
+        RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data,
+            indices: None,
+        }
+    }
+
+    #[test]
+    fn should_reuse_encoder_for_multiple_frames() {
+        let mut encoder = Encoder::new(3, 2, SHARD as u32).unwrap();
+
+        for seed in [1u8, 50, 200] {
+            let frame = frame(seed);
+            for i in 0..frame.length {
+                encoder.add_original(frame.chunk_at(i)).unwrap();
+            }
+
+            let encoded = encoder.encode_recovery().unwrap();
+            let expected = rs_encode(2, SHARD, frame).unwrap();
+
+            assert_eq!(encoded.length, expected.length);
+            assert_eq!(encoded.indices, expected.indices);
+            assert_eq!(encoded.data, expected.data);
+        }
+    }
+
+    #[test]
+    fn should_reset_encoder_with_new_parameters() {
+        let mut encoder = Encoder::new(2, 2, SHARD as u32).unwrap();
+        encoder.reset(3, 4, SHARD as u32).unwrap();
+
+        let frame = frame(7);
+        for i in 0..frame.length {
+            encoder.add_original(frame.chunk_at(i)).unwrap();
+        }
+
+        let encoded = encoder.encode_recovery().unwrap();
+        let expected = rs_encode(4, SHARD, frame).unwrap();
+
+        assert_eq!(encoded.indices, expected.indices);
+        assert_eq!(encoded.data, expected.data);
+    }
+
+    #[test]
+    fn should_decode_shards_added_out_of_order() {
+        let original = frame(1);
+        let encoded = rs_encode(3, SHARD, frame(1)).unwrap();
+        let mut decoder = Decoder::new(3, 3, SHARD as u32).unwrap();
+
+        decoder.add_recovery(2, encoded.chunk_at(2)).unwrap();
+        decoder.add_original(1, original.chunk_at(1)).unwrap();
+        decoder.add_recovery(0, encoded.chunk_at(0)).unwrap();
+
+        let decoded = decoder.decode_restored().unwrap();
+
+        assert_eq!(decoded.length, 2);
+        assert_eq!(decoded.chunk_index_at(0), 0);
+        assert_eq!(decoded.chunk_at(0), original.chunk_at(0));
+        assert_eq!(decoded.chunk_index_at(1), 2);
+        assert_eq!(decoded.chunk_at(1), original.chunk_at(2));
+    }
+
+    #[test]
+    fn should_report_readiness_as_shards_arrive() {
+        let original = frame(1);
+        let encoded = rs_encode(3, SHARD, frame(1)).unwrap();
+        let mut decoder = Decoder::new(3, 3, SHARD as u32).unwrap();
+        assert_eq!(decoder.remaining_needed(), 3);
+
+        decoder.add_recovery(1, encoded.chunk_at(1)).unwrap();
+        assert_eq!(decoder.remaining_needed(), 2);
+
+        assert!(decoder.add_recovery(1, encoded.chunk_at(1)).is_err());
+        assert_eq!(decoder.remaining_needed(), 2);
+
+        decoder.add_original(2, original.chunk_at(2)).unwrap();
+        assert_eq!(decoder.remaining_needed(), 1);
+        assert!(!decoder.ready());
+
+        decoder.add_original(0, original.chunk_at(0)).unwrap();
+        assert_eq!(decoder.remaining_needed(), 0);
+        assert!(decoder.ready());
+
+        let decoded = decoder.decode_restored().unwrap();
+        assert_eq!(decoded.chunk_at(0), original.chunk_at(1));
+        assert_eq!(decoder.remaining_needed(), 3);
+    }
+}
@@ -0,0 +1,457 @@
+//! Information about (and selection of) the computation engine used by `reed_solomon`.
+
+use reed_solomon::engine::{Engine, NoSimd};
+use reed_solomon::rate::{DefaultRateDecoder, DefaultRateEncoder, RateDecoder, RateEncoder};
+use reed_solomon::{ReedSolomonDecoder, ReedSolomonEncoder};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    add_originals, add_shards, check_decoder_input, check_encoder_input, recovery_collection,
+    restored_collection, rs_decode, rs_encode, RsError, RsErrorCode, RsShardsCollection,
+    ShardDecoder, ShardEncoder, Shards, ShardsCollection,
+};
+
+/// Name of the engine reported when no SIMD acceleration is available.
+const NO_SIMD: &str = "NoSimd";
+
+/// Check whether encoding and decoding are SIMD-accelerated on this host.
+///
+/// NOTE `reed_solomon` has no SIMD engine for WASM, so on wasm32 (i.e. in the `.wasm`
+/// artifact) this is always `false`, even if the host supports WASM SIMD.
+/// Use [`built_with_simd`] to check whether the artifact was built with `simd128`
+/// (which only lets the compiler auto-vectorize the scalar engine).
+#[wasm_bindgen]
+pub fn simd_supported() -> bool {
+    detect_engine() != NO_SIMD
+}
+
+/// Name of the engine `reed_solomon` picks on this host.
+///
+/// One of `"Avx2"`, `"Ssse3"`, `"Neon"` or `"NoSimd"`, the latter always on wasm32
+/// (see [`simd_supported`]).
+#[wasm_bindgen]
+pub fn active_engine() -> String {
+    detect_engine().into()
+}
+
+/// Check whether this library was compiled with the WASM `simd128` target feature.
+///
+/// Unlike [`simd_supported`] this reports the build configuration, not the host,
+/// so it can be used to verify that the right `.wasm` artifact got deployed.
+#[wasm_bindgen]
+pub fn built_with_simd() -> bool {
+    cfg!(target_feature = "simd128")
+}
+
+/// Initialize the engine ahead of time, so that the first real encoding is fast.
+///
+/// Builds the lookup tables (which are shared by all encoders and decoders)
+/// and runs a throw-away encoding with given parameters.
+/// It's a best-effort optimization, coding works the same without calling it.
+#[wasm_bindgen]
+pub fn prewarm(original_count: u16, recovery_count: u16, shard_bytes: u32) -> Result<(), RsError> {
+    rs_prewarm(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+    )
+}
+
+fn rs_prewarm(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+) -> Result<(), RsError> {
+    let mut encoder = ReedSolomonEncoder::new(original_count, recovery_count, shard_bytes)?;
+    let zeros = vec![0u8; shard_bytes];
+    for _ in 0..original_count {
+        encoder.add_original_shard(&zeros)?;
+    }
+    encoder.encode()?;
+    ReedSolomonDecoder::new(original_count, recovery_count, shard_bytes)?;
+
+    Ok(())
+}
+
+/// Mirror the runtime detection done by `reed_solomon`'s `DefaultEngine`.
+///
+/// `reed_solomon` doesn't expose the engine it picked, so the preference order
+/// (the fastest available engine) is repeated here, but the availability checks
+/// are the same ones [`encode_with_engine`] relies on.
+fn detect_engine() -> &'static str {
+    [("avx2", "Avx2"), ("ssse3", "Ssse3"), ("neon", "Neon")]
+        .into_iter()
+        .find(|(name, _)| parse_engine(name).is_ok())
+        .map_or(NO_SIMD, |(_, engine)| engine)
+}
+
+/// Same as [`crate::encode`], but with an explicitly chosen engine.
+///
+/// `engine` is one of `"auto"` (the default, same as [`crate::encode`]), `"scalar"`
+/// (no SIMD), `"ssse3"`, `"avx2"` or `"neon"`. Requesting an engine which is not
+/// supported on this host is an error. All engines produce identical results,
+/// so it's mostly useful to rule out a SIMD-specific problem.
+#[wasm_bindgen]
+pub fn encode_with_engine(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    engine: Option<String>,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_encode_with_engine(
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        engine.as_deref().unwrap_or("auto"),
+    )?;
+
+    Ok(result.into())
+}
+
+fn rs_encode_with_engine(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    engine: &str,
+) -> Result<RsShardsCollection, RsError> {
+    match parse_engine(engine)? {
+        EngineChoice::Auto => rs_encode(recovery_count, shard_bytes, shards),
+        EngineChoice::Scalar => encode_using(NoSimd::new(), recovery_count, shard_bytes, shards),
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        EngineChoice::Avx2 => encode_using(
+            reed_solomon::engine::Avx2::new(),
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        EngineChoice::Ssse3 => encode_using(
+            reed_solomon::engine::Ssse3::new(),
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+        #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+        EngineChoice::Neon => encode_using(
+            reed_solomon::engine::Neon::new(),
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+    }
+}
+
+/// Same as [`crate::decode`], but with an explicitly chosen engine.
+///
+/// Accepts the same `engine` names as [`encode_with_engine`], so a problem
+/// specific to one engine can be isolated on the decoding side as well.
+#[wasm_bindgen]
+pub fn decode_with_engine(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    engine: Option<String>,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_decode_with_engine(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        engine.as_deref().unwrap_or("auto"),
+    )?;
+
+    Ok(result.into())
+}
+
+fn rs_decode_with_engine(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    engine: &str,
+) -> Result<RsShardsCollection, RsError> {
+    match parse_engine(engine)? {
+        EngineChoice::Auto => rs_decode(original_count, recovery_count, shard_bytes, shards),
+        EngineChoice::Scalar => decode_using(
+            NoSimd::new(),
+            original_count,
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        EngineChoice::Avx2 => decode_using(
+            reed_solomon::engine::Avx2::new(),
+            original_count,
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        EngineChoice::Ssse3 => decode_using(
+            reed_solomon::engine::Ssse3::new(),
+            original_count,
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+        #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+        EngineChoice::Neon => decode_using(
+            reed_solomon::engine::Neon::new(),
+            original_count,
+            recovery_count,
+            shard_bytes,
+            shards,
+        ),
+    }
+}
+
+/// Engine requested by name (see [`encode_with_engine`]).
+///
+/// SIMD engines are only available when supported by the host.
+enum EngineChoice {
+    Auto,
+    Scalar,
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    Avx2,
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    Ssse3,
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    Neon,
+}
+
+fn parse_engine(engine: &str) -> Result<EngineChoice, RsError> {
+    match engine {
+        "auto" => Ok(EngineChoice::Auto),
+        "scalar" => Ok(EngineChoice::Scalar),
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        "avx2" if is_x86_feature_detected!("avx2") => Ok(EngineChoice::Avx2),
+        #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+        "ssse3" if is_x86_feature_detected!("ssse3") => Ok(EngineChoice::Ssse3),
+        #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+        "neon" if std::arch::is_aarch64_feature_detected!("neon") => Ok(EngineChoice::Neon),
+        "avx2" | "ssse3" | "neon" => Err(RsError::new(
+            RsErrorCode::Unsupported,
+            format!("engine {:?} is not supported on this host", engine),
+        )),
+        _ => Err(RsError::new(
+            RsErrorCode::Unsupported,
+            format!("unknown engine {:?}", engine),
+        )),
+    }
+}
+
+impl<E: Engine> ShardEncoder for DefaultRateEncoder<E> {
+    fn add_original(&mut self, shard: &[u8]) -> Result<(), reed_solomon::Error> {
+        self.add_original_shard(shard)
+    }
+}
+
+impl<E: Engine> ShardDecoder for DefaultRateDecoder<E> {
+    fn add_original(&mut self, index: usize, shard: &[u8]) -> Result<(), reed_solomon::Error> {
+        self.add_original_shard(index, shard)
+    }
+
+    fn add_recovery(&mut self, index: usize, shard: &[u8]) -> Result<(), reed_solomon::Error> {
+        self.add_recovery_shard(index, shard)
+    }
+}
+
+/// Encode original `shards` using given `engine`.
+fn encode_using<E: Engine>(
+    engine: E,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    check_encoder_input(recovery_count, shard_bytes, &shards)?;
+
+    let original_count = shards.count();
+    let mut encoder =
+        DefaultRateEncoder::new(original_count, recovery_count, shard_bytes, engine, None)?;
+    add_originals(&mut encoder, &shards, |_| Ok(()))?;
+    let result = encoder.encode()?;
+
+    Ok(recovery_collection(
+        original_count,
+        recovery_count,
+        shards.shard_len(),
+        result.recovery_iter(),
+    ))
+}
+
+/// Restore missing original shards using given `engine`.
+fn decode_using<E: Engine>(
+    engine: E,
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    check_decoder_input(original_count, shard_bytes, &shards)?;
+
+    let mut decoder =
+        DefaultRateDecoder::new(original_count, recovery_count, shard_bytes, engine, None)?;
+    add_shards(
+        &mut decoder,
+        original_count,
+        recovery_count,
+        &shards,
+        false,
+        |_| Ok(()),
+    )?;
+    let result = decoder.decode()?;
+
+    Ok(restored_collection(
+        shards.shard_len(),
+        result.restored_original_iter(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARD: usize = 64;
+
+    fn originals() -> RsShardsCollection {
+        RsShardsCollection {
+            length: 5,
+            shard_len: SHARD as u32,
+            data: (0..5 * SHARD).map(|i| (i * 7 % 256) as u8).collect(),
+            indices: None,
+        }
+    }
+
+    #[test]
+    fn should_report_known_engine() {
+        let engine = active_engine();
+
+        assert!(["Avx2", "Ssse3", "Neon", "NoSimd"].contains(&engine.as_str()));
+        assert_eq!(simd_supported(), engine != "NoSimd");
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn should_report_build_configuration() {
+        // native builds are never WASM SIMD builds
+        assert!(!built_with_simd());
+    }
+
+    #[test]
+    fn should_encode_identically_with_scalar_engine() {
+        let auto = rs_encode_with_engine(3, SHARD, originals(), "auto").unwrap();
+        let scalar = rs_encode_with_engine(3, SHARD, originals(), "scalar").unwrap();
+
+        assert_eq!(scalar.indices, auto.indices);
+        assert_eq!(scalar.data, auto.data);
+    }
+
+    #[test]
+    fn should_encode_identically_with_active_engine() {
+        let engine = active_engine().to_lowercase();
+        if engine == "nosimd" {
+            return;
+        }
+
+        let auto = rs_encode_with_engine(3, SHARD, originals(), "auto").unwrap();
+        let simd = rs_encode_with_engine(3, SHARD, originals(), &engine).unwrap();
+
+        assert_eq!(simd.data, auto.data);
+    }
+
+    /// Shards 0 and 3 of `originals()` lost, replaced by recovery shards.
+    fn damaged() -> RsShardsCollection {
+        let originals = originals();
+        let encoded = rs_encode(3, SHARD, &originals).unwrap();
+
+        RsShardsCollection {
+            length: 5,
+            shard_len: SHARD as u32,
+            data: [
+                encoded.chunk_at(2),
+                originals.chunk_at(1),
+                originals.chunk_at(2),
+                encoded.chunk_at(0),
+                originals.chunk_at(4),
+            ]
+            .concat(),
+            indices: Some(vec![7, 1, 2, 5, 4]),
+        }
+    }
+
+    #[test]
+    fn should_decode_identically_with_scalar_engine() {
+        let auto = rs_decode_with_engine(5, 3, SHARD, damaged(), "auto").unwrap();
+        let scalar = rs_decode_with_engine(5, 3, SHARD, damaged(), "scalar").unwrap();
+
+        assert_eq!(scalar.indices, Some(vec![0, 3]));
+        assert_eq!(scalar.indices, auto.indices);
+        assert_eq!(scalar.data, auto.data);
+        assert_eq!(scalar.chunk_at(1), originals().chunk_at(3));
+    }
+
+    #[test]
+    fn should_decode_identically_with_active_engine() {
+        let engine = active_engine().to_lowercase();
+        if engine == "nosimd" {
+            return;
+        }
+
+        let auto = rs_decode_with_engine(5, 3, SHARD, damaged(), "auto").unwrap();
+        let simd = rs_decode_with_engine(5, 3, SHARD, damaged(), &engine).unwrap();
+
+        assert_eq!(simd.indices, auto.indices);
+        assert_eq!(simd.data, auto.data);
+    }
+
+    #[test]
+    fn should_prewarm_engine() {
+        assert_eq!(rs_prewarm(5, 3, SHARD), Ok(()));
+        // twice is fine too
+        assert_eq!(rs_prewarm(5, 3, SHARD), Ok(()));
+
+        let err = rs_prewarm(5, 3, SHARD + 1).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardSize);
+    }
+
+    #[test]
+    fn should_reject_unknown_engine() {
+        let err = rs_encode_with_engine(3, SHARD, originals(), "gpu").unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::Unsupported);
+        assert_eq!(err.message(), "unknown engine \"gpu\"");
+
+        let err = rs_decode_with_engine(5, 3, SHARD, damaged(), "gpu").unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::Unsupported);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn should_report_no_simd_engine_on_wasm() {
+        assert_eq!(active_engine(), NO_SIMD);
+        assert!(!simd_supported());
+    }
+
+    #[wasm_bindgen_test]
+    #[cfg(target_feature = "simd128")]
+    fn should_report_simd_build() {
+        use core::arch::wasm32::{u8x16_extract_lane, u8x16_splat};
+
+        // the module only instantiates if it really uses (and the host runs) SIMD instructions
+        assert_eq!(u8x16_extract_lane::<3>(u8x16_splat(7)), 7);
+        assert!(built_with_simd());
+    }
+
+    #[wasm_bindgen_test]
+    #[cfg(not(target_feature = "simd128"))]
+    fn should_report_non_simd_build() {
+        assert!(!built_with_simd());
+    }
+}
@@ -0,0 +1,147 @@
+use reed_solomon::Error;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Numeric discriminant of [`RsError`].
+///
+/// Mirrors the variants of [`reed_solomon::Error`] so that JS callers can
+/// react to a specific failure without parsing the error message.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RsErrorCode {
+    DifferentShardSize = 1,
+    DuplicateOriginalShardIndex = 2,
+    DuplicateRecoveryShardIndex = 3,
+    InvalidOriginalShardIndex = 4,
+    InvalidRecoveryShardIndex = 5,
+    InvalidShardSize = 6,
+    NotEnoughShards = 7,
+    TooFewOriginalShards = 8,
+    TooManyOriginalShards = 9,
+    UnsupportedShardCount = 10,
+    // Errors below are reported by this crate rather than `reed_solomon`.
+    InvalidShardsCollection = 11,
+    InvalidPayloadLength = 12,
+    OutOfBounds = 13,
+    CallbackFailed = 14,
+    InvalidFrame = 15,
+    Unsupported = 16,
+    TimedOut = 17,
+    ReadFailed = 18,
+}
+
+/// Error returned (thrown on the JS side) by functions of this library.
+///
+/// The `code` can be compared against [`RsErrorCode`] values,
+/// the `message` is meant for humans (logs).
+/// Errors with [`RsErrorCode::NotEnoughShards`] code also carry
+/// the number of shards provided (`have`) and required (`need`).
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RsError {
+    code: RsErrorCode,
+    message: String,
+    /// Number of shards provided and required (if that's the problem).
+    shards: Option<(u32, u32)>,
+}
+
+#[wasm_bindgen]
+impl RsError {
+    /// Kind of the error.
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> RsErrorCode {
+        self.code
+    }
+
+    /// Human-readable description of the error.
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    /// Number of (distinct) shards provided, if there were not enough of them.
+    #[wasm_bindgen(getter)]
+    pub fn have(&self) -> Option<u32> {
+        self.shards.map(|(have, _)| have)
+    }
+
+    /// Number of shards required to decode, if there were not enough of them.
+    #[wasm_bindgen(getter)]
+    pub fn need(&self) -> Option<u32> {
+        self.shards.map(|(_, need)| need)
+    }
+}
+
+impl RsError {
+    pub(crate) fn new(code: RsErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            shards: None,
+        }
+    }
+
+    /// A [`RsErrorCode::NotEnoughShards`] error with the shard counts attached.
+    pub(crate) fn not_enough_shards(have: usize, need: usize, message: impl Into<String>) -> Self {
+        Self {
+            shards: Some((have as u32, need as u32)),
+            ..Self::new(RsErrorCode::NotEnoughShards, message)
+        }
+    }
+}
+
+impl core::fmt::Display for RsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<Error> for RsError {
+    fn from(value: Error) -> Self {
+        if let Error::NotEnoughShards {
+            original_count,
+            original_received_count,
+            recovery_received_count,
+        } = value
+        {
+            let have = original_received_count + recovery_received_count;
+            return Self::not_enough_shards(have, original_count, value.to_string());
+        }
+
+        let code = match value {
+            Error::DifferentShardSize { .. } => RsErrorCode::DifferentShardSize,
+            Error::DuplicateOriginalShardIndex { .. } => RsErrorCode::DuplicateOriginalShardIndex,
+            Error::DuplicateRecoveryShardIndex { .. } => RsErrorCode::DuplicateRecoveryShardIndex,
+            Error::InvalidOriginalShardIndex { .. } => RsErrorCode::InvalidOriginalShardIndex,
+            Error::InvalidRecoveryShardIndex { .. } => RsErrorCode::InvalidRecoveryShardIndex,
+            Error::InvalidShardSize { .. } => RsErrorCode::InvalidShardSize,
+            Error::NotEnoughShards { .. } => RsErrorCode::NotEnoughShards,
+            Error::TooFewOriginalShards { .. } => RsErrorCode::TooFewOriginalShards,
+            Error::TooManyOriginalShards { .. } => RsErrorCode::TooManyOriginalShards,
+            Error::UnsupportedShardCount { .. } => RsErrorCode::UnsupportedShardCount,
+        };
+
+        Self::new(code, value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_attach_shard_counts_to_not_enough_shards() {
+        let err = RsError::from(Error::NotEnoughShards {
+            original_count: 5,
+            original_received_count: 1,
+            recovery_received_count: 2,
+        });
+
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+        assert_eq!(err.have(), Some(3));
+        assert_eq!(err.need(), Some(5));
+
+        let err = RsError::from(Error::InvalidShardSize { shard_bytes: 3 });
+        assert_eq!(err.have(), None);
+        assert_eq!(err.need(), None);
+    }
+}
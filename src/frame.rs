@@ -0,0 +1,416 @@
+//! Self-describing binary frame of a [`ShardsCollection`].
+//!
+//! Layout (all integers little-endian):
+//! - magic bytes `RSSC`,
+//! - format version (`u8`),
+//! - `shard_len` (`u32`),
+//! - `length` (`u32`),
+//! - flags (`u8`, bit `0` set if indices are present, bit `1` set if config is present,
+//!   bit `2` set if `payload_len` is present),
+//! - config (`original_count` and `recovery_count` as `u16`, `shard_bytes` as `u32`, only if present),
+//! - `payload_len` (`u32`, only if present),
+//! - indices (`length` times `u16`, only if present),
+//! - data (`length * shard_len` bytes).
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys;
+
+use crate::{
+    check_collection, check_payload_len, CodingConfig, RsError, RsErrorCode, RsShardsCollection,
+    ShardsCollection,
+};
+
+const MAGIC: &[u8; 4] = b"RSSC";
+/// Version `1` stored `shard_len` (and `shard_bytes` of the config) as `u16`.
+const VERSION: u8 = 2;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + 4 + 1;
+const FLAG_INDICES: u8 = 1;
+const FLAG_CONFIG: u8 = 2;
+const CONFIG_LEN: usize = 8;
+const FLAG_PAYLOAD_LEN: u8 = 4;
+const PAYLOAD_LEN_LEN: usize = 4;
+
+#[wasm_bindgen]
+impl ShardsCollection {
+    /// Write the whole collection (including `shard_len`, indices, config and `payload_len`) into one buffer.
+    ///
+    /// Use [`ShardsCollection::deserialize`] to read it back.
+    #[wasm_bindgen]
+    pub fn serialize(&self) -> js_sys::Uint8Array {
+        let collection = RsShardsCollection {
+            length: self.length as usize,
+            shard_len: self.shard_len,
+            data: self.data.to_vec(),
+            indices: self.indices.as_ref().map(|i| i.to_vec()),
+        };
+
+        rs_serialize(&collection, self.config, self.payload_len)
+            .as_slice()
+            .into()
+    }
+
+    /// Read a collection written by [`ShardsCollection::serialize`].
+    #[wasm_bindgen]
+    pub fn deserialize(bytes: js_sys::Uint8Array) -> Result<ShardsCollection, RsError> {
+        let (collection, config, payload_len) = rs_deserialize(&bytes.to_vec())?;
+
+        let mut collection: ShardsCollection = collection.into();
+        collection.config = config;
+        collection.payload_len = payload_len;
+        Ok(collection)
+    }
+}
+
+fn rs_serialize(
+    collection: &RsShardsCollection,
+    config: Option<CodingConfig>,
+    payload_len: Option<u32>,
+) -> Vec<u8> {
+    let indices_len = collection.indices.as_ref().map_or(0, |i| i.len() * 2);
+    let mut out = Vec::with_capacity(
+        HEADER_LEN + CONFIG_LEN + PAYLOAD_LEN_LEN + indices_len + collection.data.len(),
+    );
+
+    out.extend(MAGIC);
+    out.push(VERSION);
+    out.extend(collection.shard_len.to_le_bytes());
+    out.extend((collection.length as u32).to_le_bytes());
+    let mut flags = 0;
+    if collection.indices.is_some() {
+        flags |= FLAG_INDICES;
+    }
+    if config.is_some() {
+        flags |= FLAG_CONFIG;
+    }
+    if payload_len.is_some() {
+        flags |= FLAG_PAYLOAD_LEN;
+    }
+    out.push(flags);
+    if let Some(config) = config {
+        out.extend(config.original_count.to_le_bytes());
+        out.extend(config.recovery_count.to_le_bytes());
+        out.extend(config.shard_bytes.to_le_bytes());
+    }
+    if let Some(payload_len) = payload_len {
+        out.extend(payload_len.to_le_bytes());
+    }
+    for idx in collection.indices.iter().flatten() {
+        out.extend(idx.to_le_bytes());
+    }
+    out.extend(&collection.data);
+
+    out
+}
+
+/// Collection, config and `payload_len` read from a frame.
+type Frame = (RsShardsCollection, Option<CodingConfig>, Option<u32>);
+
+fn rs_deserialize(bytes: &[u8]) -> Result<Frame, RsError> {
+    let invalid = |msg: String| Err(RsError::new(RsErrorCode::InvalidFrame, msg));
+
+    if bytes.len() < HEADER_LEN {
+        return invalid(format!(
+            "frame is too short: got {} bytes, the header alone is {}",
+            bytes.len(),
+            HEADER_LEN
+        ));
+    }
+    let (header, body) = bytes.split_at(HEADER_LEN);
+    if &header[..4] != MAGIC {
+        return invalid("not a shards collection frame (bad magic bytes)".into());
+    }
+    if header[4] != VERSION {
+        return invalid(format!("unsupported frame version {}", header[4]));
+    }
+    let shard_len = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+    let length = u32::from_le_bytes([header[9], header[10], header[11], header[12]]) as usize;
+    let has_indices = header[13] & FLAG_INDICES != 0;
+    let has_config = header[13] & FLAG_CONFIG != 0;
+    let has_payload_len = header[13] & FLAG_PAYLOAD_LEN != 0;
+
+    let (config, body) = if has_config {
+        if body.len() < CONFIG_LEN {
+            return invalid("frame is too short to contain the config".into());
+        }
+        let (config, body) = body.split_at(CONFIG_LEN);
+        let config = CodingConfig {
+            original_count: u16::from_le_bytes([config[0], config[1]]),
+            recovery_count: u16::from_le_bytes([config[2], config[3]]),
+            shard_bytes: u32::from_le_bytes([config[4], config[5], config[6], config[7]]),
+        };
+        (Some(config), body)
+    } else {
+        (None, body)
+    };
+    let (payload_len, body) = if has_payload_len {
+        if body.len() < PAYLOAD_LEN_LEN {
+            return invalid("frame is too short to contain the payload_len".into());
+        }
+        let (payload_len, body) = body.split_at(PAYLOAD_LEN_LEN);
+        let payload_len = u32::from_le_bytes([
+            payload_len[0],
+            payload_len[1],
+            payload_len[2],
+            payload_len[3],
+        ]);
+        (Some(payload_len), body)
+    } else {
+        (None, body)
+    };
+
+    // computed with 64-bit arithmetic, so a bogus `length` can't overflow
+    let indices_len = if has_indices { length as u64 * 2 } else { 0 };
+    let expected = indices_len + length as u64 * shard_len as u64;
+    if body.len() as u64 != expected {
+        return invalid(format!(
+            "invalid frame body length: got {} bytes, expected {}",
+            body.len(),
+            expected
+        ));
+    }
+    let (indices, data) = body.split_at(indices_len as usize);
+    let indices = has_indices.then(|| {
+        indices
+            .chunks_exact(2)
+            .map(|idx| u16::from_le_bytes([idx[0], idx[1]]))
+            .collect()
+    });
+    // same invariants as a collection created by the constructor
+    let Ok(data_len) = u32::try_from(data.len()) else {
+        return invalid("frame data is too long".into());
+    };
+    let valid = check_collection(shard_len, length as u32, data_len, indices.as_deref())
+        .and_then(|_| payload_len.map_or(Ok(()), |len| check_payload_len(len, data_len)));
+    if let Err(e) = valid {
+        return invalid(format!("invalid shards collection: {}", e.message()));
+    }
+
+    let collection = RsShardsCollection {
+        length,
+        shard_len,
+        data: data.to_vec(),
+        indices,
+    };
+    Ok((collection, config, payload_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(indices: Option<Vec<u16>>) -> RsShardsCollection {
+        RsShardsCollection {
+            length: 3,
+            shard_len: 4,
+            data: (0..12).collect(),
+            indices,
+        }
+    }
+
+    #[test]
+    fn should_round_trip_collection_with_indices() {
+        let original = collection(Some(vec![7, 1, 300]));
+
+        let frame = rs_serialize(&original, None, None);
+        assert_eq!(frame.len(), HEADER_LEN + 6 + 12);
+
+        let (decoded, config, _) = rs_deserialize(&frame).unwrap();
+        assert_eq!(config, None);
+        assert_eq!(decoded.length, 3);
+        assert_eq!(decoded.shard_len, 4);
+        assert_eq!(decoded.indices, original.indices);
+        assert_eq!(decoded.data, original.data);
+    }
+
+    #[test]
+    fn should_round_trip_collection_without_indices() {
+        let original = collection(None);
+
+        let frame = rs_serialize(&original, None, None);
+        assert_eq!(frame.len(), HEADER_LEN + 12);
+
+        let (decoded, config, _) = rs_deserialize(&frame).unwrap();
+        assert_eq!(config, None);
+        assert_eq!(decoded.length, 3);
+        assert_eq!(decoded.shard_len, 4);
+        assert_eq!(decoded.indices, None);
+        assert_eq!(decoded.data, original.data);
+    }
+
+    #[test]
+    fn should_reject_bad_magic() {
+        let mut frame = rs_serialize(&collection(None), None, None);
+        frame[0] = b'X';
+
+        let err = rs_deserialize(&frame).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+    }
+
+    #[test]
+    fn should_reject_truncated_frame() {
+        let frame = rs_serialize(&collection(Some(vec![0, 1, 2])), None, None);
+
+        for len in [0, HEADER_LEN - 1, HEADER_LEN, frame.len() - 1] {
+            let err = rs_deserialize(&frame[..len]).unwrap_err();
+            assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_config() {
+        let original = collection(Some(vec![0, 1, 2]));
+        let config = CodingConfig {
+            original_count: 2,
+            recovery_count: 1,
+            shard_bytes: 4,
+        };
+
+        let frame = rs_serialize(&original, Some(config), None);
+        assert_eq!(frame.len(), HEADER_LEN + CONFIG_LEN + 6 + 12);
+
+        let (decoded, decoded_config, _) = rs_deserialize(&frame).unwrap();
+        assert_eq!(decoded_config, Some(config));
+        assert_eq!(decoded.indices, original.indices);
+        assert_eq!(decoded.data, original.data);
+
+        for len in [HEADER_LEN + 1, frame.len() - 1] {
+            let err = rs_deserialize(&frame[..len]).unwrap_err();
+            assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+        }
+    }
+
+    #[test]
+    fn should_round_trip_shard_len_above_u16() {
+        let shard_len = 100 * 1024;
+        let original = RsShardsCollection {
+            length: 2,
+            shard_len,
+            data: (0..2 * shard_len).map(|i| i as u8).collect(),
+            indices: None,
+        };
+        let config = CodingConfig {
+            original_count: 2,
+            recovery_count: 1,
+            shard_bytes: shard_len,
+        };
+
+        let frame = rs_serialize(&original, Some(config), None);
+        let (decoded, decoded_config, _) = rs_deserialize(&frame).unwrap();
+        assert_eq!(decoded_config, Some(config));
+        assert_eq!(decoded.shard_len, shard_len);
+        assert_eq!(decoded.data, original.data);
+    }
+
+    #[test]
+    fn should_reject_previous_frame_version() {
+        let mut frame = rs_serialize(&collection(None), None, None);
+        frame[4] = 1;
+
+        let err = rs_deserialize(&frame).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+        assert_eq!(err.message(), "unsupported frame version 1");
+    }
+
+    #[test]
+    fn should_reject_frame_violating_collection_invariants() {
+        // `shard_len = 0` and `length = u32::MAX` with an empty body
+        let mut frame = rs_serialize(&collection(None), None, None);
+        frame[5..9].copy_from_slice(&0u32.to_le_bytes());
+        frame[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+        frame.truncate(HEADER_LEN);
+
+        let err = rs_deserialize(&frame).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+
+        let frame = rs_serialize(&collection(Some(vec![1, 5, 1])), None, None);
+        let err = rs_deserialize(&frame).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+        assert_eq!(
+            err.message(),
+            "invalid shards collection: duplicate shard index 1"
+        );
+    }
+
+    #[test]
+    fn should_round_trip_payload_len() {
+        let original = collection(Some(vec![0, 1, 2]));
+        let config = CodingConfig {
+            original_count: 2,
+            recovery_count: 1,
+            shard_bytes: 4,
+        };
+
+        let frame = rs_serialize(&original, Some(config), Some(7));
+        assert_eq!(
+            frame.len(),
+            HEADER_LEN + CONFIG_LEN + PAYLOAD_LEN_LEN + 6 + 12
+        );
+
+        let (decoded, decoded_config, payload_len) = rs_deserialize(&frame).unwrap();
+        assert_eq!(decoded_config, Some(config));
+        assert_eq!(payload_len, Some(7));
+        assert_eq!(decoded.indices, original.indices);
+        assert_eq!(decoded.data, original.data);
+
+        let (_, _, payload_len) = rs_deserialize(&rs_serialize(&original, None, None)).unwrap();
+        assert_eq!(payload_len, None);
+    }
+
+    #[test]
+    fn should_reject_payload_len_beyond_data() {
+        let frame = rs_serialize(&collection(None), None, Some(13));
+
+        let err = rs_deserialize(&frame).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidFrame);
+        assert_eq!(
+            err.message(),
+            "invalid shards collection: payload_len (13) exceeds the length of the data (12)"
+        );
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::{encode_systematic, protect, recover};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn should_keep_config_of_encoded_collection() {
+        let data: Vec<u8> = (0..3 * 64).map(|i| i as u8).collect();
+        let originals = ShardsCollection::new(64, data.as_slice().into(), None, None).unwrap();
+        let encoded = encode_systematic(2, 64, originals).unwrap();
+
+        let config = CodingConfig {
+            original_count: 3,
+            recovery_count: 2,
+            shard_bytes: 64,
+        };
+        assert_eq!(encoded.config(), Some(config));
+
+        let decoded = ShardsCollection::deserialize(encoded.serialize()).unwrap();
+        assert_eq!(decoded.config(), Some(config));
+        assert_eq!(decoded.data.to_vec(), encoded.data.to_vec());
+    }
+
+    #[wasm_bindgen_test]
+    fn should_keep_payload_len_of_protected_payload() {
+        let data: Vec<u8> = (0..150).map(|i| i as u8).collect();
+        let protected = protect(data.as_slice().into(), 64, 2, None, None).unwrap();
+
+        let decoded = ShardsCollection::deserialize(protected.serialize()).unwrap();
+        assert_eq!(decoded.payload_len(), Some(150));
+        let config = decoded.config().unwrap();
+
+        let recovered = recover(
+            config.original_count,
+            config.recovery_count,
+            config.shard_bytes,
+            decoded.payload_len().unwrap(),
+            decoded,
+            None,
+        )
+        .unwrap();
+        assert_eq!(recovered.to_vec(), data);
+    }
+}
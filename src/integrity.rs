@@ -0,0 +1,261 @@
+//! Opt-in integrity layer: per-shard CRC32 checksums.
+//!
+//! Reed-Solomon only recovers from erasures (missing shards), so corrupted
+//! shards must be detected and dropped before decoding.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    rs_decode_full, rs_encode, RsError, RsErrorCode, RsShardsCollection, Shards, ShardsCollection,
+};
+
+/// Length of the checksum appended to every shard.
+const CRC_BYTES: usize = 4;
+
+/// Lookup table of the (reflected) CRC-32/ISO-HDLC polynomial.
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Compute CRC32 (as used by zlib, PNG, etc.) of `data`.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finalize()
+}
+
+/// Incremental [`crc32`], for data which is not in one contiguous buffer.
+pub(crate) struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    pub(crate) fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    /// Feed next part of the data.
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.state =
+                CRC_TABLE[((self.state ^ *byte as u32) & 0xff) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    /// CRC32 of all of the data fed so far.
+    pub(crate) fn finalize(self) -> u32 {
+        !self.state
+    }
+}
+
+/// Same as [`crate::encode_systematic`], but every shard carries a checksum.
+///
+/// A 4-byte CRC32 (little-endian) is appended to every original and recovery shard,
+/// so the shards of the result are `shard_bytes + 4` long.
+/// Use [`decode_with_crc`] to decode them.
+#[wasm_bindgen]
+pub fn encode_with_crc(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_encode_with_crc(recovery_count as usize, shard_bytes as usize, shards)?;
+
+    Ok(result.into())
+}
+
+/// Restore all original shards out of shards produced by [`encode_with_crc`].
+///
+/// `shards` must be `shard_bytes + 4` long. Shards with a checksum mismatch are
+/// treated as missing, so corrupted shards don't spoil the result as long as enough
+/// intact shards remain. The result (like [`crate::decode_full`]'s) contains
+/// all `original_count` shards in index order, without the checksums.
+#[wasm_bindgen]
+pub fn decode_with_crc(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_decode_with_crc(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Length of a shard with the checksum appended.
+fn checked_shard_len(shard_bytes: usize) -> Result<u32, RsError> {
+    shard_bytes
+        .checked_add(CRC_BYTES)
+        .and_then(|shard_len| u32::try_from(shard_len).ok())
+        .ok_or_else(|| {
+            RsError::new(
+                RsErrorCode::InvalidShardSize,
+                format!(
+                    "shard_bytes ({}) is too large to append a checksum",
+                    shard_bytes
+                ),
+            )
+        })
+}
+
+fn rs_encode_with_crc(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    let shard_len = checked_shard_len(shard_bytes)?;
+    let recovery = rs_encode(recovery_count, shard_bytes, &shards)?;
+
+    let length = shards.count() + recovery.length;
+    let mut data = Vec::with_capacity(length * shard_len as usize);
+    let mut push = |chunk: &[u8]| {
+        data.extend(chunk);
+        data.extend(crc32(chunk).to_le_bytes());
+    };
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        push(shards.read_chunk(i, &mut scratch));
+    }
+    for i in 0..recovery.length {
+        push(recovery.chunk_at(i));
+    }
+
+    Ok(RsShardsCollection {
+        length,
+        shard_len,
+        data,
+        indices: Some((0..length as u16).collect()),
+    })
+}
+
+fn rs_decode_with_crc(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    let shard_len = checked_shard_len(shard_bytes)?;
+    if shards.shard_len() != shard_len {
+        return Err(RsError::new(
+            RsErrorCode::DifferentShardSize,
+            format!(
+                "shard_len ({}) must be shard_bytes ({}) plus {} bytes of checksum",
+                shards.shard_len(),
+                shard_bytes,
+                CRC_BYTES
+            ),
+        ));
+    }
+
+    let mut data = vec![];
+    let mut indices = vec![];
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        let (chunk, crc) = shards.read_chunk(i, &mut scratch).split_at(shard_bytes);
+        if crc32(chunk).to_le_bytes() == crc {
+            data.extend(chunk);
+            indices.push(shards.index_at(i));
+        }
+    }
+
+    let intact = RsShardsCollection {
+        length: indices.len(),
+        shard_len: shard_bytes as u32,
+        data,
+        indices: Some(indices),
+    };
+    rs_decode_full(original_count, recovery_count, shard_bytes, intact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARD: usize = 64;
+
+    fn originals() -> RsShardsCollection {
+        RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data: (0..4 * SHARD).map(|i| (i % 251) as u8).collect(),
+            indices: None,
+        }
+    }
+
+    /// Flip one byte of shard at `position`.
+    fn corrupt(shards: &mut RsShardsCollection, position: usize) {
+        shards.data[position * shards.shard_len as usize + 7] ^= 0x40;
+    }
+
+    #[test]
+    fn should_compute_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let mut crc = Crc32::new();
+        for part in [&b"1234"[..], b"", b"56789"] {
+            crc.update(part);
+        }
+        assert_eq!(crc.finalize(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn should_append_checksum_to_every_shard() {
+        let encoded = rs_encode_with_crc(2, SHARD, originals()).unwrap();
+
+        assert_eq!(encoded.length, 6);
+        assert_eq!(encoded.shard_len as usize, SHARD + CRC_BYTES);
+        assert_eq!(&encoded.chunk_at(1)[..SHARD], originals().chunk_at(1));
+        for i in 0..encoded.length {
+            let (chunk, crc) = encoded.chunk_at(i).split_at(SHARD);
+            assert_eq!(crc, crc32(chunk).to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn should_recover_from_corrupted_shards() {
+        let mut encoded = rs_encode_with_crc(2, SHARD, originals()).unwrap();
+        corrupt(&mut encoded, 1);
+        corrupt(&mut encoded, 4);
+
+        let decoded = rs_decode_with_crc(4, 2, SHARD, encoded).unwrap();
+
+        assert_eq!(decoded.shard_len as usize, SHARD);
+        assert_eq!(decoded.data, originals().data);
+    }
+
+    #[test]
+    fn should_fail_with_too_many_corrupted_shards() {
+        let mut encoded = rs_encode_with_crc(2, SHARD, originals()).unwrap();
+        corrupt(&mut encoded, 0);
+        corrupt(&mut encoded, 2);
+        corrupt(&mut encoded, 5);
+
+        let err = rs_decode_with_crc(4, 2, SHARD, encoded).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+    }
+}
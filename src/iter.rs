@@ -0,0 +1,236 @@
+//! Handing out shards one at a time, instead of as a single collection.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys;
+
+use crate::{rs_decode, rs_encode, RsError, RsShardsCollection, Shards, ShardsCollection};
+
+/// Recovery shards handed out one by one (see [`encode_iter`]).
+///
+/// NOTE the object holds WASM memory, so make sure to call `free()`
+/// on the JS side once it's not needed anymore (or drain it).
+#[wasm_bindgen]
+pub struct RecoveryIterator {
+    shards: RsShardsCollection,
+    position: usize,
+}
+
+#[wasm_bindgen]
+impl RecoveryIterator {
+    /// Copy the next recovery shard to JS, or `undefined` when there are no more shards.
+    ///
+    /// Recovery shards are yielded in index order.
+    #[wasm_bindgen]
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<js_sys::Uint8Array> {
+        self.next_shard().map(Into::into)
+    }
+
+    /// Number of shards not yet returned by `next`.
+    #[wasm_bindgen(getter)]
+    pub fn remaining(&self) -> usize {
+        self.shards.length - self.position
+    }
+}
+
+impl RecoveryIterator {
+    fn next_shard(&mut self) -> Option<&[u8]> {
+        if self.position == self.shards.length {
+            return None;
+        }
+        self.position += 1;
+
+        Some(self.shards.chunk_at(self.position - 1))
+    }
+}
+
+/// Restored original shards handed out one by one (see [`decode_iter`]).
+///
+/// NOTE the object holds WASM memory, so make sure to call `free()`
+/// on the JS side once it's not needed anymore (or drain it).
+#[wasm_bindgen]
+pub struct RestoredIterator {
+    shards: RsShardsCollection,
+    position: usize,
+}
+
+#[wasm_bindgen]
+impl RestoredIterator {
+    /// Copy the next restored shard to JS as an `[index, Uint8Array]` pair,
+    /// or `undefined` when there are no more shards.
+    ///
+    /// Restored shards are yielded in index order.
+    #[wasm_bindgen]
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<js_sys::Array> {
+        self.next_entry().map(|(index, shard)| {
+            js_sys::Array::of2(&index.into(), &js_sys::Uint8Array::from(shard))
+        })
+    }
+
+    /// Number of shards not yet returned by `next`.
+    #[wasm_bindgen(getter)]
+    pub fn remaining(&self) -> usize {
+        self.shards.length - self.position
+    }
+}
+
+impl RestoredIterator {
+    fn next_entry(&mut self) -> Option<(u16, &[u8])> {
+        if self.position == self.shards.length {
+            return None;
+        }
+        self.position += 1;
+
+        let position = self.position - 1;
+        Some((
+            self.shards.chunk_index_at(position),
+            self.shards.chunk_at(position),
+        ))
+    }
+}
+
+/// Same as [`crate::encode`], but the recovery shards are copied to JS one at a time.
+///
+/// NOTE the encoding itself happens up front (the recovery shards are computed
+/// together) and all of them are kept in WASM memory (`recovery_count * shard_bytes`
+/// bytes, on top of the work buffer of the encoder while encoding) until the
+/// iterator is freed. What's saved is the copy of all of them in JS,
+/// since `next` copies out one shard at a time.
+/// The first recovery shard has index `original_count`.
+#[wasm_bindgen]
+pub fn encode_iter(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<RecoveryIterator, RsError> {
+    rs_encode_iter(recovery_count as usize, shard_bytes as usize, shards)
+}
+
+fn rs_encode_iter(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RecoveryIterator, RsError> {
+    Ok(RecoveryIterator {
+        shards: rs_encode(recovery_count, shard_bytes, shards)?,
+        position: 0,
+    })
+}
+
+/// Same as [`crate::decode`], but the restored shards are copied to JS one at a time.
+///
+/// NOTE the decoding itself happens up front (the missing shards are restored
+/// together) and the iterator only walks the result kept in WASM memory,
+/// so every restored shard can be written out (e.g. to disk or network)
+/// without copying all of them to JS first.
+#[wasm_bindgen]
+pub fn decode_iter(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<RestoredIterator, RsError> {
+    rs_decode_iter(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )
+}
+
+fn rs_decode_iter(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RestoredIterator, RsError> {
+    Ok(RestoredIterator {
+        shards: rs_decode(original_count, recovery_count, shard_bytes, shards)?,
+        position: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARD: usize = 64;
+
+    fn originals() -> RsShardsCollection {
+        RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data: (0..4 * SHARD).map(|i| (i * 5 % 256) as u8).collect(),
+            indices: None,
+        }
+    }
+
+    #[test]
+    fn should_yield_same_shards_as_encode() {
+        let expected = rs_encode(6, SHARD, originals()).unwrap();
+        let mut iter = rs_encode_iter(6, SHARD, originals()).unwrap();
+        assert_eq!(iter.remaining(), 6);
+
+        let mut collected = vec![];
+        while let Some(shard) = iter.next_shard() {
+            collected.extend(shard);
+        }
+
+        assert_eq!(collected, expected.data);
+        assert_eq!(iter.remaining(), 0);
+        assert_eq!(iter.next_shard(), None);
+    }
+
+    #[test]
+    fn should_yield_same_shards_as_decode() {
+        let originals = originals();
+        let encoded = rs_encode(2, SHARD, &originals).unwrap();
+        // originals 0 and 2 are missing
+        let shards = RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data: [
+                encoded.chunk_at(1),
+                originals.chunk_at(1),
+                originals.chunk_at(3),
+                encoded.chunk_at(0),
+            ]
+            .concat(),
+            indices: Some(vec![5, 1, 3, 4]),
+        };
+
+        let expected = rs_decode(4, 2, SHARD, &shards).unwrap();
+        let mut iter = rs_decode_iter(4, 2, SHARD, &shards).unwrap();
+        assert_eq!(iter.remaining(), 2);
+
+        let mut collected = vec![];
+        while let Some((index, shard)) = iter.next_entry() {
+            collected.push((index, shard.to_vec()));
+        }
+
+        let expected: Vec<_> = (0..expected.length)
+            .map(|i| (expected.chunk_index_at(i), expected.chunk_at(i).to_vec()))
+            .collect();
+        assert_eq!(collected, expected);
+        assert_eq!(collected[0], (0, originals.chunk_at(0).to_vec()));
+        assert_eq!(collected[1], (2, originals.chunk_at(2).to_vec()));
+        assert_eq!(iter.next_entry(), None);
+    }
+
+    #[test]
+    fn should_drop_partially_consumed_iterator() {
+        let expected = rs_encode(3, SHARD, originals()).unwrap();
+
+        for taken in 0..=3 {
+            let mut iter = rs_encode_iter(3, SHARD, originals()).unwrap();
+            for i in 0..taken {
+                assert_eq!(iter.next_shard(), Some(expected.chunk_at(i)));
+            }
+            assert_eq!(iter.remaining(), 3 - taken);
+        }
+
+        let err = rs_encode_iter(3, SHARD + 2, originals()).err().unwrap();
+        assert_eq!(err.code(), crate::RsErrorCode::DifferentShardSize);
+    }
+}
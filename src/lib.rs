@@ -1,9 +1,52 @@
-use reed_solomon::Error;
 use reed_solomon::ReedSolomonDecoder;
 use reed_solomon::ReedSolomonEncoder;
 use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
 use web_sys::js_sys;
 
+mod bench;
+mod builder;
+mod codec;
+mod engine;
+mod error;
+mod frame;
+mod integrity;
+mod iter;
+mod limits;
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+mod partial;
+mod payload;
+mod plan;
+mod progress;
+mod raw;
+mod report;
+
+pub use bench::benchmark;
+pub use builder::ShardsCollectionBuilder;
+pub use codec::{Decoder, Encoder};
+pub use engine::{
+    active_engine, built_with_simd, decode_with_engine, encode_with_engine, prewarm, simd_supported,
+};
+pub use error::{RsError, RsErrorCode};
+pub use integrity::{decode_with_crc, encode_with_crc};
+pub use iter::{decode_iter, encode_iter, RecoveryIterator, RestoredIterator};
+pub use limits::{
+    estimate_memory_bytes, max_original_count, max_recovery_count, max_shards, min_shard_bytes,
+    shard_bytes_multiple,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{decode_bytes, encode_bytes};
+pub use partial::{decode_partial, PartialDecode};
+pub use payload::{
+    max_payload_bytes, padding_len, protect, recover, shard_bytes_for, split_file, split_payload,
+};
+pub use plan::{can_decode, minimal_shards_to_keep, missing_original_indices};
+pub use progress::{decode_with_deadline, encode_async, encode_with_progress};
+pub use raw::encode_ptr;
+pub use report::{decode_verbose, DecodeReport};
+
 /// Collection of shards (either input or output).
 ///
 /// To efficiently pass data between JS and WASM all of the shards
@@ -16,42 +59,235 @@ use web_sys::js_sys;
 /// in `data` array.
 ///
 /// This collection is only used to get the data from JS or pass the data back.
-/// Internally we convert it to [`RsShardsCollection`], which copies
-/// the memory to/from WASM.
+/// Encoding and decoding read the shards straight from JS memory (one shard
+/// at a time, see [`Shards`]), while results are built in WASM as
+/// [`RsShardsCollection`] and copied to JS once.
 #[wasm_bindgen]
 pub struct ShardsCollection {
     /// Number of shards within the collection.
     pub length: u32,
     /// The length of each shard.
-    pub shard_len: u16,
+    pub shard_len: u32,
     /// All shards concatenated.
     data: js_sys::Uint8Array,
     /// Optional indices for shards in the collection.
     indices: Option<js_sys::Uint16Array>,
+    /// Number of meaningful bytes, if the shards were created from a padded payload.
+    payload_len: Option<u32>,
+    /// Parameters of the encoding the shards were created with (if known).
+    config: Option<CodingConfig>,
+    /// Indices of original shards restored by the decoder (only set by `decode_full`).
+    restored: Option<Vec<u16>>,
+}
+
+/// Parameters the shards of a [`ShardsCollection`] were encoded with.
+///
+/// Enough to [`decode`] the shards without passing the parameters separately.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CodingConfig {
+    pub original_count: u16,
+    pub recovery_count: u16,
+    pub shard_bytes: u32,
 }
 
 #[wasm_bindgen]
 impl ShardsCollection {
+    /// Create a collection of shards concatenated in `data`.
+    ///
+    /// `payload_len` is the number of meaningful bytes in `data`, in case the
+    /// shards were created from a payload zero-padded to a multiple of `shard_len`.
+    /// All shards (including the last one) still have to be `shard_len` long.
     #[wasm_bindgen(constructor)]
     pub fn new(
-        shard_len: u16,
+        shard_len: u32,
         data: js_sys::Uint8Array,
         indices: Option<js_sys::Uint16Array>,
-    ) -> Self {
-        let length = data.length() / shard_len as u32;
-        if let Some(ref indices) = indices {
-            assert!(
-                indices.length() == length,
-                "Mismatching indices and data length."
-            );
+        payload_len: Option<u32>,
+    ) -> Result<ShardsCollection, RsError> {
+        let length = shards_count(
+            shard_len,
+            data.length(),
+            indices.as_ref().map(|i| i.length()),
+        )?;
+        if let Some(payload_len) = payload_len {
+            check_payload_len(payload_len, data.length())?;
         }
 
-        Self {
+        Ok(Self {
             length,
             shard_len,
             indices,
             data,
+            payload_len,
+            config: None,
+            restored: None,
+        })
+    }
+
+    /// Create a collection from an array of separate shards (`Uint8Array`s).
+    ///
+    /// All of the shards must have the same, non-zero length, which becomes
+    /// the `shard_len` of the collection. The shards are copied into
+    /// one contiguous buffer.
+    #[wasm_bindgen]
+    pub fn from_chunks(
+        chunks: js_sys::Array,
+        indices: Option<js_sys::Uint16Array>,
+    ) -> Result<ShardsCollection, RsError> {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| chunk.dyn_into::<js_sys::Uint8Array>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                RsError::new(
+                    RsErrorCode::InvalidShardsCollection,
+                    "All chunks must be Uint8Arrays.",
+                )
+            })?;
+        let shard_len = common_shard_len(chunks.iter().map(|chunk| chunk.length()))?;
+
+        let data = js_sys::Uint8Array::new_with_length(chunks.len() as u32 * shard_len);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            data.set(chunk, idx as u32 * shard_len);
         }
+
+        Self::new(shard_len, data, indices, None)
+    }
+
+    /// Same as the constructor, but for shards meant to be decoded with given parameters.
+    ///
+    /// Additionally rejects any index `>= original_count + recovery_count`
+    /// (naming the index and its position), so that invalid input is caught
+    /// when the collection is created rather than when it's decoded.
+    #[wasm_bindgen]
+    pub fn new_for_decode(
+        shard_len: u32,
+        data: js_sys::Uint8Array,
+        indices: Option<js_sys::Uint16Array>,
+        original_count: u16,
+        recovery_count: u16,
+    ) -> Result<ShardsCollection, RsError> {
+        let collection = Self::new(shard_len, data, indices, None)?;
+        let total_count = original_count as usize + recovery_count as usize;
+        for position in 0..collection.count() {
+            check_index_in_range(
+                collection.index_at(position) as usize,
+                position,
+                total_count,
+            )?;
+        }
+
+        Ok(collection)
+    }
+
+    /// Create a collection of shards concatenated in `buffer`.
+    ///
+    /// Same as the constructor, but accepts an `ArrayBuffer` (e.g. from `fetch().arrayBuffer()`).
+    /// NOTE the buffer is NOT copied, the collection is a view of it,
+    /// so modifications of the buffer are visible in the collection.
+    #[wasm_bindgen]
+    pub fn from_array_buffer(
+        shard_len: u32,
+        buffer: js_sys::ArrayBuffer,
+        indices: Option<js_sys::Uint16Array>,
+    ) -> Result<ShardsCollection, RsError> {
+        Self::new(shard_len, js_sys::Uint8Array::new(&buffer), indices, None)
+    }
+
+    /// Same as the constructor, but with the expected number of shards given explicitly.
+    ///
+    /// Fails if `data` is not exactly `count * shard_len` long, which catches
+    /// e.g. an off-by-one buffer that would otherwise be a valid (but different) collection.
+    #[wasm_bindgen]
+    pub fn from_flat_with_count(
+        shard_len: u32,
+        count: u32,
+        data: js_sys::Uint8Array,
+        indices: Option<js_sys::Uint16Array>,
+    ) -> Result<ShardsCollection, RsError> {
+        check_shards_count(shard_len, count, data.length())?;
+
+        Self::new(shard_len, data, indices, None)
+    }
+
+    /// Create a collection of `count` zero-filled shards of `shard_len` bytes.
+    ///
+    /// Meant to be filled in place with `set_shard` as the shards arrive
+    /// (and `with_indices` once their indices are known).
+    #[wasm_bindgen]
+    pub fn zeros(shard_len: u32, count: usize) -> Result<ShardsCollection, RsError> {
+        let data_len = u32::try_from(count as u64 * shard_len as u64).map_err(|_| {
+            RsError::new(
+                RsErrorCode::InvalidShardsCollection,
+                format!("{} shards of {} bytes are too large", count, shard_len),
+            )
+        })?;
+
+        Self::new(
+            shard_len,
+            js_sys::Uint8Array::new_with_length(data_len),
+            None,
+            None,
+        )
+    }
+
+    /// Create a collection of shards concatenated in any `ArrayBufferView`.
+    ///
+    /// Accepts e.g. a `Uint8ClampedArray` (`ImageData`), an `Int8Array` or a `DataView`,
+    /// i.e. views with 1-byte elements (or no elements at all).
+    /// NOTE the data is NOT copied, the collection is a view of the same memory.
+    #[wasm_bindgen]
+    pub fn from_typed_array(
+        shard_len: u32,
+        view: JsValue,
+        indices: Option<js_sys::Uint16Array>,
+    ) -> Result<ShardsCollection, RsError> {
+        Self::new(shard_len, bytes_of_view(&view)?, indices, None)
+    }
+
+    /// Length of the payload the shards were created from (see `protect`).
+    ///
+    /// Returns `None` if the collection wasn't created from a payload.
+    #[wasm_bindgen(getter)]
+    pub fn payload_len(&self) -> Option<u32> {
+        self.payload_len
+    }
+
+    /// Parameters the shards were encoded with (see `encode_systematic`).
+    ///
+    /// Returns `None` if they are not known.
+    #[wasm_bindgen(getter)]
+    pub fn config(&self) -> Option<CodingConfig> {
+        self.config
+    }
+
+    /// Number of original shards of the encoding (if `config` is known).
+    #[wasm_bindgen(getter)]
+    pub fn original_count(&self) -> Option<u16> {
+        self.config.map(|config| config.original_count)
+    }
+
+    /// Number of recovery shards of the encoding (if `config` is known).
+    #[wasm_bindgen(getter)]
+    pub fn recovery_count(&self) -> Option<u16> {
+        self.config.map(|config| config.recovery_count)
+    }
+
+    /// Indices of the original shards which were restored (rather than copied from the input).
+    ///
+    /// Only known for results of `decode_full`.
+    #[wasm_bindgen(getter)]
+    pub fn restored_indices(&self) -> Option<js_sys::Uint16Array> {
+        self.restored.as_deref().map(Into::into)
+    }
+
+    /// Number of distinct shards (of any kind) sufficient to decode (if `config` is known).
+    ///
+    /// That's the same as `original_count`: any `original_count` shards of the encoding suffice.
+    #[wasm_bindgen(getter)]
+    pub fn shards_needed(&self) -> Option<u16> {
+        self.original_count()
     }
 
     /// Extract the `indices` from this shards container.
@@ -63,6 +299,29 @@ impl ShardsCollection {
         self.indices.take()
     }
 
+    /// Attach explicit `indices` to the shards, replacing the current ones.
+    ///
+    /// `indices` must have exactly one entry per shard.
+    /// NOTE this object is consumed, use the returned collection instead.
+    #[wasm_bindgen]
+    pub fn with_indices(self, indices: js_sys::Uint16Array) -> Result<ShardsCollection, RsError> {
+        if indices.length() != self.length {
+            return Err(RsError::new(
+                RsErrorCode::InvalidShardsCollection,
+                format!(
+                    "indices length ({}) does not match the number of shards ({})",
+                    indices.length(),
+                    self.length
+                ),
+            ));
+        }
+
+        Ok(Self {
+            indices: Some(indices),
+            ..self
+        })
+    }
+
     /// Take the underlying `data` to the JS side.
     ///
     /// NOTE this object is destroyed after the data is consumed,
@@ -72,34 +331,570 @@ impl ShardsCollection {
         self.data
     }
 
-    // THESE METHODS SHOULD RATHER BE IMPLEMENTED IN JS!
-    /*
-        #[wasm_bindgen(getter)]
-        pub fn len(&self) -> usize {
-            self.length as usize
+    /// Drop the references to the (potentially large) `data` and `indices` arrays.
+    ///
+    /// The collection becomes empty (`shard_len` is kept), so the arrays can be
+    /// garbage-collected as soon as no other JS references to them exist
+    /// (e.g. views returned by `chunk_at`).
+    /// NOTE that `free()` still has to be called to release the object itself.
+    #[wasm_bindgen]
+    pub fn release_data(&mut self) {
+        self.data = js_sys::Uint8Array::new_with_length(0);
+        self.indices = None;
+        self.length = 0;
+        self.payload_len = None;
+    }
+
+    /// Split the collection into separate shards.
+    ///
+    /// NOTE that the returned `Uint8Array`s are views (`subarray`) of the
+    /// backing buffer, i.e. no data is copied, but the shards alias
+    /// the collection's data and are only valid as long as the data is.
+    #[wasm_bindgen]
+    pub fn to_chunks(&self) -> js_sys::Array {
+        (0..self.length).map(|idx| self.shard_view(idx)).collect()
+    }
+
+    /// List `[index, shard]` pairs of all shards in the collection.
+    ///
+    /// The `index` is the shard index (see `indices`) and `shard`
+    /// is a view of the backing buffer (same as in [`ShardsCollection::to_chunks`]).
+    #[wasm_bindgen]
+    pub fn entries(&self) -> js_sys::Array {
+        (0..self.length)
+            .map(|idx| js_sys::Array::of2(&self.shard_index(idx).into(), &self.shard_view(idx)))
+            .collect()
+    }
+
+    /// Number of shards in the collection.
+    #[wasm_bindgen(getter)]
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    /// Whether there are no shards in the collection.
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Size of the underlying data in bytes (`len * shard_len`).
+    #[wasm_bindgen(getter)]
+    pub fn total_bytes(&self) -> usize {
+        self.data.length() as usize
+    }
+
+    /// Get shard data of chunk at index `index`.
+    ///
+    /// NOTE the returned array is a view of the collection's data (no copy).
+    /// Use `chunk_index_at` to get the shard index of that chunk.
+    #[wasm_bindgen]
+    pub fn chunk_at(&self, index: usize) -> Result<js_sys::Uint8Array, RsError> {
+        let (begin, end) = shard_range(index, self.shard_len, self.data.length())?;
+
+        Ok(self.data.subarray(begin, end))
+    }
+
+    /// Copy shard data of chunk at index `index` into `out`.
+    ///
+    /// Unlike `chunk_at`, which returns a view aliasing the collection's
+    /// data, `out` is owned by the caller and stays valid (and unchanged)
+    /// when the collection is modified or freed.
+    /// `out` must be exactly `shard_len` long.
+    #[wasm_bindgen]
+    pub fn copy_shard_into(&self, index: usize, out: js_sys::Uint8Array) -> Result<(), RsError> {
+        if out.length() != self.shard_len {
+            return Err(RsError::new(
+                RsErrorCode::DifferentShardSize,
+                format!(
+                    "output length ({}) does not match shard_len ({})",
+                    out.length(),
+                    self.shard_len
+                ),
+            ));
+        }
+        let (begin, end) = shard_range(index, self.shard_len, self.data.length())?;
+        out.set(&self.data.subarray(begin, end), 0);
+
+        Ok(())
+    }
+
+    /// Retrieve the shard index of given chunk.
+    ///
+    /// This method will default to returning `index`
+    /// if the `indices` array is not provided.
+    /// Fails if `index` is out of bounds (of the collection or the `indices` array).
+    #[wasm_bindgen]
+    pub fn chunk_index_at(&self, index: usize) -> Result<u16, RsError> {
+        if index >= self.length as usize {
+            return Err(RsError::new(
+                RsErrorCode::OutOfBounds,
+                format!(
+                    "Shard {} is out of bounds (length: {}).",
+                    index, self.length
+                ),
+            ));
+        }
+        if let Some(ref indices) = self.indices {
+            if index >= indices.length() as usize {
+                return Err(RsError::new(
+                    RsErrorCode::OutOfBounds,
+                    format!(
+                        "Shard {} has no index (indices length: {}).",
+                        index,
+                        indices.length()
+                    ),
+                ));
+            }
+        }
+
+        Ok(self.shard_index(index as u32))
+    }
+
+    /// Overwrite data of the chunk at position `slot` with `bytes`.
+    ///
+    /// `bytes` must be exactly `shard_len` long. Together with a zero-filled
+    /// collection it allows assembling the shards in place as they arrive.
+    #[wasm_bindgen]
+    pub fn set_shard(&mut self, slot: usize, bytes: js_sys::Uint8Array) -> Result<(), RsError> {
+        if bytes.length() != self.shard_len {
+            return Err(RsError::new(
+                RsErrorCode::DifferentShardSize,
+                format!(
+                    "shard length ({}) does not match shard_len ({})",
+                    bytes.length(),
+                    self.shard_len
+                ),
+            ));
+        }
+        let (begin, _) = shard_range(slot, self.shard_len, self.data.length())?;
+        self.data.set(&bytes, begin);
+
+        Ok(())
+    }
+
+    /// Create a new collection of shards at positions `[start, end)`.
+    ///
+    /// The data is copied. The resulting collection always carries explicit
+    /// indices, so the shards keep their original indices even if
+    /// this collection relies on the implicit ones.
+    #[wasm_bindgen]
+    pub fn slice(&self, start: usize, end: usize) -> Result<ShardsCollection, RsError> {
+        if start > end || end > self.length as usize {
+            return Err(RsError::new(
+                RsErrorCode::OutOfBounds,
+                format!(
+                    "Invalid range [{}, {}) of a collection with {} shards.",
+                    start, end, self.length
+                ),
+            ));
+        }
+
+        let (start, end) = (start as u32, end as u32);
+        let shard_len = self.shard_len;
+        let indices = self.explicit_indices().slice(start, end);
+
+        Ok(Self {
+            length: end - start,
+            shard_len: self.shard_len,
+            data: self.data.slice(start * shard_len, end * shard_len),
+            indices: Some(indices),
+            payload_len: None,
+            config: self.config,
+            restored: None,
+        })
+    }
+
+    /// Create a new collection containing shards of this collection followed by shards of `other`.
+    ///
+    /// Both collections must have the same `shard_len`. The data is copied.
+    /// The resulting collection always carries explicit indices, so that
+    /// every shard keeps its index (implicit indices are materialized).
+    #[wasm_bindgen]
+    pub fn concat(&self, other: &ShardsCollection) -> Result<ShardsCollection, RsError> {
+        if self.shard_len != other.shard_len {
+            return Err(RsError::new(
+                RsErrorCode::DifferentShardSize,
+                format!(
+                    "Cannot concat collections with different shard_len ({} and {}).",
+                    self.shard_len, other.shard_len
+                ),
+            ));
+        }
+
+        let data = js_sys::Uint8Array::new_with_length(self.data.length() + other.data.length());
+        data.set(&self.data, 0);
+        data.set(&other.data, self.data.length());
+
+        let indices = js_sys::Uint16Array::new_with_length(self.length + other.length);
+        indices.set(&self.explicit_indices(), 0);
+        indices.set(&other.explicit_indices(), self.length);
+
+        Ok(Self {
+            length: self.length + other.length,
+            shard_len: self.shard_len,
+            data,
+            indices: Some(indices),
+            payload_len: None,
+            config: self.config.filter(|config| other.config == Some(*config)),
+            restored: None,
+        })
+    }
+
+    /// Create an independent copy of the collection.
+    ///
+    /// NOTE this allocates new buffers for both the data and indices.
+    #[wasm_bindgen(js_name = clone)]
+    pub fn deep_clone(&self) -> ShardsCollection {
+        Self {
+            length: self.length,
+            shard_len: self.shard_len,
+            data: self.data.slice(0, self.data.length()),
+            indices: self.indices.as_ref().map(|i| i.slice(0, i.length())),
+            payload_len: self.payload_len,
+            config: self.config,
+            restored: self.restored.clone(),
+        }
+    }
+
+    /// Re-initialize the collection in place with new shards.
+    ///
+    /// Accepts the same arguments (and performs the same validation) as the constructor,
+    /// so one object can be re-used for a stream of frames.
+    /// On error the collection is left unchanged.
+    #[wasm_bindgen]
+    pub fn reset(
+        &mut self,
+        shard_len: u32,
+        data: js_sys::Uint8Array,
+        indices: Option<js_sys::Uint16Array>,
+    ) -> Result<(), RsError> {
+        *self = Self::new(shard_len, data, indices, None)?;
+
+        Ok(())
+    }
+
+    /// Check that the collection is well-formed.
+    ///
+    /// Verifies that `data` consists of exactly `length` shards of `shard_len` bytes
+    /// and that `indices` (if present) has `length` distinct entries.
+    #[wasm_bindgen]
+    pub fn validate(&self) -> Result<(), RsError> {
+        check_collection(
+            self.shard_len,
+            self.length,
+            self.data.length(),
+            self.indices.as_ref().map(|i| i.to_vec()).as_deref(),
+        )
+    }
+
+    /// CRC32 of the collection's content: `shard_len`, `length`, indices and data.
+    ///
+    /// Collections with the same shards (and indices, explicit or implicit)
+    /// have the same checksum. Meant for quick comparisons or transfer checks,
+    /// it's not a cryptographic digest.
+    #[wasm_bindgen]
+    pub fn checksum(&self) -> u32 {
+        shards_checksum(self)
+    }
+
+    /// Check whether both collections contain the same shards.
+    ///
+    /// Compares `shard_len`, `length`, indices and the data byte-for-byte.
+    /// Implicit indices are equal to explicit sequential ones (`0, 1, 2, ...`).
+    /// Unlike `checksum` it can't report a false match.
+    #[wasm_bindgen]
+    pub fn equals(&self, other: &ShardsCollection) -> bool {
+        shards_equal(self, other)
+    }
+
+    /// Create a new collection with only the recovery shards (index `>= original_count`).
+    ///
+    /// The data is copied and the shards keep their (absolute) indices, so it's
+    /// handy to send just the recovery part of an `encode_systematic` result.
+    #[wasm_bindgen]
+    pub fn recovery_only(&self, original_count: usize) -> ShardsCollection {
+        self.filter_by_index(|idx| idx as usize >= original_count)
+    }
+
+    /// Create a new collection with only the original shards (index `< original_count`).
+    ///
+    /// The data is copied and the shards keep their indices. The complement of `recovery_only`.
+    #[wasm_bindgen]
+    pub fn originals_only(&self, original_count: usize) -> ShardsCollection {
+        self.filter_by_index(|idx| (idx as usize) < original_count)
+    }
+}
+
+impl ShardsCollection {
+    /// Copy shards whose index satisfies `keep` (in order) into a new collection.
+    fn filter_by_index(&self, keep: impl Fn(u16) -> bool) -> ShardsCollection {
+        let positions: Vec<u32> = (0..self.length)
+            .filter(|position| keep(self.shard_index(*position)))
+            .collect();
+
+        let shard_len = self.shard_len;
+        let data = js_sys::Uint8Array::new_with_length(positions.len() as u32 * shard_len);
+        let mut indices = Vec::with_capacity(positions.len());
+        for (i, position) in positions.iter().enumerate() {
+            data.set(&self.shard_view(*position), i as u32 * shard_len);
+            indices.push(self.shard_index(*position));
+        }
+
+        Self {
+            length: positions.len() as u32,
+            shard_len: self.shard_len,
+            data,
+            indices: Some(indices.as_slice().into()),
+            payload_len: None,
+            config: self.config,
+            restored: None,
+        }
+    }
+
+    /// Indices of the shards, materialized if the collection relies on the implicit ones.
+    fn explicit_indices(&self) -> js_sys::Uint16Array {
+        match self.indices {
+            Some(ref indices) => indices.clone(),
+            None => {
+                let indices: Vec<u16> = (0..self.length).map(|idx| idx as u16).collect();
+                indices.as_slice().into()
+            }
         }
+    }
+
+    /// Subarray of the data containing shard at position `idx`.
+    fn shard_view(&self, idx: u32) -> js_sys::Uint8Array {
+        let begin = idx * self.shard_len;
+        self.data.subarray(begin, begin + self.shard_len)
+    }
+
+    /// Shard index of the shard at position `idx` (defaults to `idx`).
+    fn shard_index(&self, idx: u32) -> u16 {
+        self.indices
+            .as_ref()
+            .map(|v| v.get_index(idx))
+            .unwrap_or(idx as u16)
+    }
+}
+
+/// Compute the byte range `[begin, end)` of shard at position `index`.
+///
+/// The offsets are computed with 64-bit arithmetic, so large collections
+/// can't overflow into a wrong range.
+fn shard_range(index: usize, shard_len: u32, data_len: u32) -> Result<(u32, u32), RsError> {
+    let begin = index as u64 * shard_len as u64;
+    let end = begin + shard_len as u64;
+    if end > data_len as u64 {
+        return Err(RsError::new(
+            RsErrorCode::OutOfBounds,
+            format!(
+                "Shard {} is out of bounds (data length: {}).",
+                index, data_len
+            ),
+        ));
+    }
+
+    Ok((begin as u32, end as u32))
+}
+
+/// Compute the number of shards in a collection, validating its layout.
+///
+/// The `data_len` must be an exact multiple of `shard_len` (an empty collection is fine)
+/// and `indices_len` (if present) must match the number of shards.
+fn shards_count(shard_len: u32, data_len: u32, indices_len: Option<u32>) -> Result<u32, RsError> {
+    let invalid = |msg: String| Err(RsError::new(RsErrorCode::InvalidShardsCollection, msg));
+
+    if shard_len == 0 {
+        return invalid("shard_len must be greater than 0".into());
+    }
+    if data_len % shard_len != 0 {
+        return invalid(format!(
+            "data length ({}) is not a multiple of shard_len ({})",
+            data_len, shard_len
+        ));
+    }
 
-        #[wasm_bindgen(getter)]
-        pub fn chunk_at(&self, index: usize) -> js_sys::Uint8Array {
-            let begin = index as u32 * self.shard_len as u32;
-            let end = begin + self.shard_len as u32;
-            self.data.subarray(begin, end)
+    let length = data_len / shard_len;
+    match indices_len {
+        Some(indices_len) if indices_len != length => invalid(format!(
+            "Mismatching indices ({}) and data ({}) length.",
+            indices_len, length
+        )),
+        _ => Ok(length),
+    }
+}
+
+/// `Uint8Array` aliasing the bytes of an `ArrayBufferView` with 1-byte elements.
+fn bytes_of_view(view: &JsValue) -> Result<js_sys::Uint8Array, RsError> {
+    let invalid = |msg: &str| Err(RsError::new(RsErrorCode::InvalidShardsCollection, msg));
+
+    if !js_sys::ArrayBuffer::is_view(view) {
+        return invalid("Expected a typed array or a DataView.");
+    }
+    let property = |name: &str| js_sys::Reflect::get(view, &name.into()).unwrap_or_default();
+    // `DataView`s don't have elements
+    if let Some(element_size) = property("BYTES_PER_ELEMENT").as_f64() {
+        if element_size != 1.0 {
+            return invalid("Elements of the typed array must be 1 byte long.");
         }
+    }
+
+    let offset = property("byteOffset").as_f64().unwrap_or_default() as u32;
+    let length = property("byteLength").as_f64().unwrap_or_default() as u32;
+    Ok(js_sys::Uint8Array::new_with_byte_offset_and_length(
+        &property("buffer"),
+        offset,
+        length,
+    ))
+}
+
+/// Total length of `count` shards of `shard_bytes` bytes each.
+///
+/// Fails (with given `code`) if it doesn't fit into `usize`, which on wasm32
+/// happens already for a large shard count combined with large shards.
+fn shards_len(count: usize, shard_bytes: usize, code: RsErrorCode) -> Result<usize, RsError> {
+    count.checked_mul(shard_bytes).ok_or_else(|| {
+        RsError::new(
+            code,
+            format!(
+                "{} shards of {} bytes exceed the addressable memory.",
+                count, shard_bytes
+            ),
+        )
+    })
+}
+
+/// Make sure that shard index `idx` (at `position`) is lower than `total_count`.
+fn check_index_in_range(idx: usize, position: usize, total_count: usize) -> Result<(), RsError> {
+    if idx >= total_count {
+        return Err(RsError::new(
+            RsErrorCode::InvalidRecoveryShardIndex,
+            format!(
+                "Shard index {} (at position {}) is out of range (original_count + recovery_count = {}).",
+                idx, position, total_count
+            ),
+        ));
+    }
+
+    Ok(())
+}
 
-        #[wasm_bindgen(getter)]
-        pub fn chunk_index_at(&self, index: usize) -> u16 {
-            self.indices
-                .as_ref()
-                .map(|v| v.at(index as i32).expect("Out of bounds access to indices."))
-                .unwrap_or(index as u16)
+/// Make sure that `data_len` is exactly `count` shards of `shard_len` bytes.
+fn check_shards_count(shard_len: u32, count: u32, data_len: u32) -> Result<(), RsError> {
+    let expected = count as u64 * shard_len as u64;
+    if expected != data_len as u64 {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            format!(
+                "Expected {} shards of {} bytes ({} bytes), got {} bytes.",
+                count, shard_len, expected, data_len
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the invariants of a collection (see [`ShardsCollection::validate`]).
+fn check_collection(
+    shard_len: u32,
+    length: u32,
+    data_len: u32,
+    indices: Option<&[u16]>,
+) -> Result<(), RsError> {
+    let invalid = |msg: String| Err(RsError::new(RsErrorCode::InvalidShardsCollection, msg));
+
+    let count = shards_count(shard_len, data_len, indices.map(|i| i.len() as u32))?;
+    if count != length {
+        return invalid(format!(
+            "length ({}) does not match the number of shards in data ({})",
+            length, count
+        ));
+    }
+
+    let mut seen = vec![false; u16::MAX as usize + 1];
+    for idx in indices.unwrap_or_default() {
+        if seen[*idx as usize] {
+            return invalid(format!("duplicate shard index {}", idx));
         }
-    */
+        seen[*idx as usize] = true;
+    }
+
+    Ok(())
+}
+
+/// Make sure the payload fits within `data_len` bytes of shards.
+fn check_payload_len(payload_len: u32, data_len: u32) -> Result<(), RsError> {
+    if payload_len > data_len {
+        return Err(RsError::new(
+            RsErrorCode::InvalidPayloadLength,
+            format!(
+                "payload_len ({}) exceeds the length of the data ({})",
+                payload_len, data_len
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// CRC32 of `shard_len`, `count` (both `u32` LE), indices (`u16` LE) and data of `shards`.
+///
+/// Computed incrementally, one shard at a time, so the shards are not copied
+/// into one buffer first.
+fn shards_checksum(shards: impl Shards) -> u32 {
+    let mut crc = integrity::Crc32::new();
+    crc.update(&shards.shard_len().to_le_bytes());
+    crc.update(&(shards.count() as u32).to_le_bytes());
+    for i in 0..shards.count() {
+        crc.update(&shards.index_at(i).to_le_bytes());
+    }
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        crc.update(shards.read_chunk(i, &mut scratch));
+    }
+
+    crc.finalize()
+}
+
+/// Compare shards (with their indices) of two collections.
+fn shards_equal(a: impl Shards, b: impl Shards) -> bool {
+    if a.shard_len() != b.shard_len() || a.count() != b.count() {
+        return false;
+    }
+
+    let (mut scratch_a, mut scratch_b) = (vec![], vec![]);
+    (0..a.count()).all(|i| {
+        a.index_at(i) == b.index_at(i)
+            && a.read_chunk(i, &mut scratch_a) == b.read_chunk(i, &mut scratch_b)
+    })
+}
+
+/// Determine the shard length of separately passed shards.
+///
+/// Fails if there are no shards or they have different lengths.
+fn common_shard_len(mut lengths: impl Iterator<Item = u32>) -> Result<u32, RsError> {
+    let invalid = |msg: String| Err(RsError::new(RsErrorCode::InvalidShardsCollection, msg));
+
+    let Some(first) = lengths.next() else {
+        return invalid("At least one chunk is required.".into());
+    };
+    if let Some(other) = lengths.find(|len| *len != first) {
+        return invalid(format!(
+            "All chunks must have the same length (got {} and {}).",
+            first, other
+        ));
+    }
+
+    Ok(first)
 }
 
 /// A Rust equivalent of [`ShardsCollection`].
+#[derive(Debug)]
 struct RsShardsCollection {
     pub length: usize,
-    pub shard_len: u16,
+    pub shard_len: u32,
     pub data: Vec<u8>,
     pub indices: Option<Vec<u16>>,
 }
@@ -144,6 +939,9 @@ impl From<RsShardsCollection> for ShardsCollection {
             shard_len,
             data: data.as_slice().into(),
             indices: indices.map(|i| i.as_slice().into()),
+            payload_len: None,
+            config: None,
+            restored: None,
         }
     }
 }
@@ -156,6 +954,7 @@ impl From<ShardsCollection> for RsShardsCollection {
             shard_len,
             data,
             indices,
+            ..
         } = value;
 
         Self {
@@ -167,202 +966,3108 @@ impl From<ShardsCollection> for RsShardsCollection {
     }
 }
 
-fn rs_encode(
-    recovery_count: usize,
-    shards: RsShardsCollection,
-) -> Result<RsShardsCollection, Error> {
-    let mut encoder =
-        ReedSolomonEncoder::new(shards.length, recovery_count, shards.shard_len.into())?;
-
-    for i in 0..shards.length {
-        assert!(
-            shards.chunk_index_at(i) == i as u16,
-            "Input shards must be in order!"
-        );
-        encoder.add_original_shard(shards.chunk_at(i))?;
-    }
+/// Read access to a collection of shards, regardless of where its memory lives.
+///
+/// Allows the encoder and decoder to read shards straight from JS memory
+/// (one shard at a time), instead of copying the whole collection to WASM first.
+trait Shards {
+    /// Number of shards in the collection.
+    fn count(&self) -> usize;
 
-    let result = encoder.encode()?;
+    /// The length of each shard.
+    fn shard_len(&self) -> u32;
 
-    let mut data = Vec::with_capacity(recovery_count * shards.shard_len as usize);
+    /// Shard index of the shard at position `index`.
+    fn index_at(&self, index: usize) -> u16;
 
-    let mut indices = vec![];
-    for (idx, chunk) in result.recovery_iter().enumerate() {
-        indices.push((shards.length + idx) as u16);
-        data.extend(chunk);
+    /// Data of the shard at position `index`.
+    ///
+    /// The `scratch` buffer is used in case the data needs to be copied first.
+    fn read_chunk<'a>(&'a self, index: usize, scratch: &'a mut Vec<u8>) -> &'a [u8];
+}
+
+impl Shards for RsShardsCollection {
+    fn count(&self) -> usize {
+        self.length
     }
 
-    Ok(RsShardsCollection {
-        length: recovery_count,
-        shard_len: shards.shard_len,
-        data,
-        indices: Some(indices),
-    })
-}
+    fn shard_len(&self) -> u32 {
+        self.shard_len
+    }
+
+    fn index_at(&self, index: usize) -> u16 {
+        self.chunk_index_at(index)
+    }
+
+    fn read_chunk<'a>(&'a self, index: usize, _scratch: &'a mut Vec<u8>) -> &'a [u8] {
+        self.chunk_at(index)
+    }
+}
+
+impl<T: Shards> Shards for &T {
+    fn count(&self) -> usize {
+        (*self).count()
+    }
+
+    fn shard_len(&self) -> u32 {
+        (*self).shard_len()
+    }
+
+    fn index_at(&self, index: usize) -> u16 {
+        (*self).index_at(index)
+    }
+
+    fn read_chunk<'a>(&'a self, index: usize, scratch: &'a mut Vec<u8>) -> &'a [u8] {
+        (*self).read_chunk(index, scratch)
+    }
+}
+
+/// Shards (in order) concatenated in a borrowed buffer.
+struct FlatShards<'a> {
+    shard_len: u32,
+    data: &'a [u8],
+}
+
+impl Shards for FlatShards<'_> {
+    fn count(&self) -> usize {
+        self.data
+            .len()
+            .checked_div(self.shard_len as usize)
+            .unwrap_or(0)
+    }
+
+    fn shard_len(&self) -> u32 {
+        self.shard_len
+    }
+
+    fn index_at(&self, index: usize) -> u16 {
+        index as u16
+    }
+
+    fn read_chunk<'a>(&'a self, index: usize, _scratch: &'a mut Vec<u8>) -> &'a [u8] {
+        let begin = index * self.shard_len as usize;
+        &self.data[begin..begin + self.shard_len as usize]
+    }
+}
+
+/// Original shards followed by recovery shards indexed relatively (from `0`).
+///
+/// Presents the recovery shards with absolute indices (offset by `original_count`).
+struct SplitShards<O, R> {
+    original_count: usize,
+    originals: O,
+    recoveries: R,
+}
+
+impl<O: Shards, R: Shards> Shards for SplitShards<O, R> {
+    fn count(&self) -> usize {
+        self.originals.count() + self.recoveries.count()
+    }
+
+    fn shard_len(&self) -> u32 {
+        self.originals.shard_len()
+    }
+
+    fn index_at(&self, index: usize) -> u16 {
+        match index.checked_sub(self.originals.count()) {
+            None => self.originals.index_at(index),
+            Some(index) => self.original_count as u16 + self.recoveries.index_at(index),
+        }
+    }
+
+    fn read_chunk<'a>(&'a self, index: usize, scratch: &'a mut Vec<u8>) -> &'a [u8] {
+        match index.checked_sub(self.originals.count()) {
+            None => self.originals.read_chunk(index, scratch),
+            Some(index) => self.recoveries.read_chunk(index, scratch),
+        }
+    }
+}
+
+/// Reads every shard by copying it from JS memory into the `scratch` buffer.
+impl Shards for ShardsCollection {
+    fn count(&self) -> usize {
+        self.length as usize
+    }
+
+    fn shard_len(&self) -> u32 {
+        self.shard_len
+    }
+
+    fn index_at(&self, index: usize) -> u16 {
+        self.shard_index(index as u32)
+    }
+
+    fn read_chunk<'a>(&'a self, index: usize, scratch: &'a mut Vec<u8>) -> &'a [u8] {
+        scratch.resize(self.shard_len as usize, 0);
+        self.shard_view(index as u32).copy_to(scratch);
+        scratch
+    }
+}
+
+/// Make sure that `shard_bytes` is a valid shard size (see `limits`).
+///
+/// Checked up front, so that the error is more descriptive than the one
+/// reported deep inside of the encoder or decoder.
+fn validate_shard_bytes(shard_bytes: usize) -> Result<(), RsError> {
+    let invalid = |msg: String| Err(RsError::new(RsErrorCode::InvalidShardSize, msg));
+
+    if shard_bytes == 0 {
+        return invalid("shard_bytes must be greater than 0".into());
+    }
+    if shard_bytes > limits::MAX_SHARD_BYTES {
+        return invalid(format!(
+            "shard_bytes exceeds maximum ({} > {})",
+            shard_bytes,
+            limits::MAX_SHARD_BYTES
+        ));
+    }
+    if shard_bytes % limits::SHARD_BYTES_MULTIPLE != 0 {
+        return invalid(format!(
+            "shard_bytes must be a multiple of {}",
+            limits::SHARD_BYTES_MULTIPLE
+        ));
+    }
+
+    Ok(())
+}
+
+/// Make sure that `shard_bytes` requested by the caller matches the collection.
+fn check_shard_bytes(shard_bytes: usize, shards: &impl Shards) -> Result<(), RsError> {
+    if shard_bytes != shards.shard_len() as usize {
+        return Err(RsError::new(
+            RsErrorCode::DifferentShardSize,
+            format!(
+                "shard_bytes ({}) does not match shard_len ({})",
+                shard_bytes,
+                shards.shard_len()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Make sure that a shard count is non-zero (the zero case is most likely a mistake).
+fn check_count(name: &str, count: usize) -> Result<(), RsError> {
+    if count == 0 {
+        return Err(RsError::new(
+            RsErrorCode::UnsupportedShardCount,
+            format!("{} must be at least 1", name),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate the parameters of encoding before creating an encoder.
+fn check_encoder_input(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: &impl Shards,
+) -> Result<(), RsError> {
+    if shards.count() == 0 {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            "no shards provided",
+        ));
+    }
+    check_count("recovery_count", recovery_count)?;
+    validate_shard_bytes(shard_bytes)?;
+    check_shard_bytes(shard_bytes, shards)?;
+
+    for position in 0..shards.count() {
+        let idx = shards.index_at(position);
+        if idx as usize != position {
+            return Err(RsError::new(
+                RsErrorCode::InvalidShardsCollection,
+                format!(
+                    "Original shard at position {} has index {}, input shards must be in order.",
+                    position, idx
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// An encoder original shards are fed to, regardless of the engine it uses.
+trait ShardEncoder {
+    fn add_original(&mut self, shard: &[u8]) -> Result<(), reed_solomon::Error>;
+}
+
+impl ShardEncoder for ReedSolomonEncoder {
+    fn add_original(&mut self, shard: &[u8]) -> Result<(), reed_solomon::Error> {
+        self.add_original_shard(shard)
+    }
+}
+
+/// A decoder shards are fed to, regardless of the engine it uses.
+///
+/// NOTE the index of a recovery shard is relative (the first one has index `0`).
+trait ShardDecoder {
+    fn add_original(&mut self, index: usize, shard: &[u8]) -> Result<(), reed_solomon::Error>;
+
+    fn add_recovery(&mut self, index: usize, shard: &[u8]) -> Result<(), reed_solomon::Error>;
+}
+
+impl ShardDecoder for ReedSolomonDecoder {
+    fn add_original(&mut self, index: usize, shard: &[u8]) -> Result<(), reed_solomon::Error> {
+        self.add_original_shard(index, shard)
+    }
+
+    fn add_recovery(&mut self, index: usize, shard: &[u8]) -> Result<(), reed_solomon::Error> {
+        self.add_recovery_shard(index, shard)
+    }
+}
+
+/// Create an encoder and feed it with all original `shards`.
+fn prepare_encoder(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: &impl Shards,
+) -> Result<ReedSolomonEncoder, RsError> {
+    prepare_encoder_with(recovery_count, shard_bytes, shards, |_| Ok(()))
+}
+
+/// Same as [`prepare_encoder`], but `on_added` is called with the number
+/// of original shards added so far, after every shard.
+fn prepare_encoder_with(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: &impl Shards,
+    mut on_added: impl FnMut(usize) -> Result<(), RsError>,
+) -> Result<ReedSolomonEncoder, RsError> {
+    check_encoder_input(recovery_count, shard_bytes, shards)?;
+
+    let mut encoder = ReedSolomonEncoder::new(shards.count(), recovery_count, shard_bytes)?;
+    add_originals(&mut encoder, shards, on_added)?;
+
+    Ok(encoder)
+}
+
+/// Feed the `encoder` with all original `shards` (see [`prepare_encoder_with`]).
+fn add_originals(
+    encoder: &mut impl ShardEncoder,
+    shards: &impl Shards,
+    mut on_added: impl FnMut(usize) -> Result<(), RsError>,
+) -> Result<(), RsError> {
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        add_original_at(encoder, shards, i, &mut scratch)?;
+        on_added(i + 1)?;
+    }
+
+    Ok(())
+}
+
+/// Add the original shard at `position` of `shards` to the `encoder`.
+fn add_original_at(
+    encoder: &mut impl ShardEncoder,
+    shards: &impl Shards,
+    position: usize,
+    scratch: &mut Vec<u8>,
+) -> Result<(), RsError> {
+    encoder.add_original(shards.read_chunk(position, scratch))?;
+
+    Ok(())
+}
+
+fn rs_encode(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    let mut encoder = prepare_encoder(recovery_count, shard_bytes, &shards)?;
+    let result = encoder.encode()?;
+
+    Ok(recovery_collection(
+        shards.count(),
+        recovery_count,
+        shards.shard_len(),
+        result.recovery_iter(),
+    ))
+}
+
+fn rs_encode_many(
+    recovery_count: usize,
+    shard_bytes: usize,
+    frames: impl IntoIterator<Item = impl Shards>,
+) -> Result<Vec<RsShardsCollection>, RsError> {
+    let mut encoder: Option<ReedSolomonEncoder> = None;
+    let mut scratch = vec![];
+    let mut results = vec![];
+    for shards in frames {
+        check_encoder_input(recovery_count, shard_bytes, &shards)?;
+        let encoder = match encoder {
+            Some(ref mut encoder) => {
+                encoder.reset(shards.count(), recovery_count, shard_bytes)?;
+                encoder
+            }
+            None => encoder.insert(ReedSolomonEncoder::new(
+                shards.count(),
+                recovery_count,
+                shard_bytes,
+            )?),
+        };
+
+        for i in 0..shards.count() {
+            add_original_at(encoder, &shards, i, &mut scratch)?;
+        }
+        let result = encoder.encode()?;
+        results.push(recovery_collection(
+            shards.count(),
+            recovery_count,
+            shards.shard_len(),
+            result.recovery_iter(),
+        ));
+    }
+
+    Ok(results)
+}
+
+fn rs_encode_systematic(
+    recovery_count: usize,
+    shard_bytes: usize,
+    mut shards: RsShardsCollection,
+) -> Result<RsShardsCollection, RsError> {
+    let original_count = shards.length;
+    let mut encoder = prepare_encoder(recovery_count, shard_bytes, &shards)?;
+    let result = encoder.encode()?;
+    for chunk in result.recovery_iter() {
+        shards.data.extend(chunk);
+    }
+
+    shards.length = original_count + recovery_count;
+    shards.indices = Some((0..shards.length).map(|idx| idx as u16).collect());
+
+    Ok(shards)
+}
+
+/// Encode and pass every recovery shard (with its byte offset) to `write`.
+///
+/// Fails if `output_len` is too small to fit all recovery shards.
+/// Returns the number of bytes written.
+fn rs_encode_into(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    output_len: usize,
+    mut write: impl FnMut(usize, &[u8]),
+) -> Result<usize, RsError> {
+    let required = shards_len(recovery_count, shard_bytes, RsErrorCode::OutOfBounds)?;
+    if output_len < required {
+        return Err(RsError::new(
+            RsErrorCode::OutOfBounds,
+            format!(
+                "Output buffer is too small: got {} bytes, need {}.",
+                output_len, required
+            ),
+        ));
+    }
+
+    let mut encoder = prepare_encoder(recovery_count, shard_bytes, &shards)?;
+    let result = encoder.encode()?;
+
+    let mut written = 0;
+    for chunk in result.recovery_iter() {
+        write(written, chunk);
+        written += chunk.len();
+    }
+
+    Ok(written)
+}
+
+fn rs_encode_subset(
+    recovery_count: usize,
+    shard_bytes: usize,
+    wanted: &[u16],
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    if let Some(idx) = wanted.iter().find(|idx| **idx as usize >= recovery_count) {
+        return Err(RsError::new(
+            RsErrorCode::InvalidRecoveryShardIndex,
+            format!(
+                "wanted recovery shard index ({}) must be lower than recovery_count ({})",
+                idx, recovery_count
+            ),
+        ));
+    }
+
+    let mut encoder = prepare_encoder(recovery_count, shard_bytes, &shards)?;
+    let result = encoder.encode()?;
+
+    let mut data = Vec::with_capacity(wanted.len() * shards.shard_len() as usize);
+    let mut indices = Vec::with_capacity(wanted.len());
+    for idx in wanted {
+        let chunk = result
+            .recovery(*idx as usize)
+            .expect("Index validated against recovery_count.");
+        indices.push((shards.count() + *idx as usize) as u16);
+        data.extend(chunk);
+    }
+
+    Ok(RsShardsCollection {
+        length: indices.len(),
+        shard_len: shards.shard_len(),
+        data,
+        indices: Some(indices),
+    })
+}
+
+fn rs_regenerate_recovery(
+    recovery_count: usize,
+    shard_bytes: usize,
+    target_recovery_index: u16,
+    shards: impl Shards,
+) -> Result<Vec<u8>, RsError> {
+    let result = rs_encode_subset(
+        recovery_count,
+        shard_bytes,
+        &[target_recovery_index],
+        shards,
+    )?;
+
+    Ok(result.data)
+}
+
+fn rs_verify_recovery(
+    recovery_count: usize,
+    shard_bytes: usize,
+    recovery_index: u16,
+    originals: impl Shards,
+    candidate: &[u8],
+) -> Result<bool, RsError> {
+    let expected = rs_regenerate_recovery(recovery_count, shard_bytes, recovery_index, originals)?;
+
+    Ok(expected == candidate)
+}
+
+/// Gather recovery shards produced by the encoder into a collection.
+///
+/// Recovery shards are labelled with absolute indices (starting at `original_count`).
+fn recovery_collection<'a>(
+    original_count: usize,
+    recovery_count: usize,
+    shard_len: u32,
+    recovery: impl Iterator<Item = &'a [u8]>,
+) -> RsShardsCollection {
+    let mut data = Vec::with_capacity(recovery_count * shard_len as usize);
+
+    let mut indices = vec![];
+    for (idx, chunk) in recovery.enumerate() {
+        indices.push((original_count + idx) as u16);
+        data.extend(chunk);
+    }
+
+    RsShardsCollection {
+        length: indices.len(),
+        shard_len,
+        data,
+        indices: Some(indices),
+    }
+}
+
+/// Create a decoder and feed it with all of the `shards`.
+///
+/// Shards with an index that was already seen are either rejected
+/// or (if `skip_duplicates` is set) ignored. Once `original_count` shards
+/// were added, surplus recovery shards are validated, but not added.
+fn prepare_decoder(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: &impl Shards,
+    skip_duplicates: bool,
+) -> Result<ReedSolomonDecoder, RsError> {
+    prepare_decoder_with(
+        original_count,
+        recovery_count,
+        shard_bytes,
+        shards,
+        skip_duplicates,
+        |_| Ok(()),
+    )
+}
+
+/// Same as [`prepare_decoder`], but `on_added` is called with the number
+/// of shards processed so far, after every shard.
+fn prepare_decoder_with(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: &impl Shards,
+    skip_duplicates: bool,
+    on_added: impl FnMut(usize) -> Result<(), RsError>,
+) -> Result<ReedSolomonDecoder, RsError> {
+    check_decoder_input(original_count, shard_bytes, shards)?;
+
+    let mut decoder = ReedSolomonDecoder::new(original_count, recovery_count, shard_bytes)?;
+    add_shards(
+        &mut decoder,
+        original_count,
+        recovery_count,
+        shards,
+        skip_duplicates,
+        on_added,
+    )?;
+
+    Ok(decoder)
+}
+
+/// Validate the parameters of decoding before creating a decoder.
+fn check_decoder_input(
+    original_count: usize,
+    shard_bytes: usize,
+    shards: &impl Shards,
+) -> Result<(), RsError> {
+    check_count("original_count", original_count)?;
+    validate_shard_bytes(shard_bytes)?;
+    check_shard_bytes(shard_bytes, shards)
+}
+
+/// Feed the `decoder` with `shards` (see [`prepare_decoder_with`]).
+fn add_shards(
+    decoder: &mut impl ShardDecoder,
+    original_count: usize,
+    recovery_count: usize,
+    shards: &impl Shards,
+    skip_duplicates: bool,
+    mut on_added: impl FnMut(usize) -> Result<(), RsError>,
+) -> Result<(), RsError> {
+    let mut seen = vec![false; original_count + recovery_count];
+    let mut added = 0;
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        let idx = shards.index_at(i) as usize;
+        check_index_in_range(idx, i, original_count + recovery_count)?;
+
+        if seen[idx] {
+            if skip_duplicates {
+                continue;
+            }
+            let code = if idx < original_count {
+                RsErrorCode::DuplicateOriginalShardIndex
+            } else {
+                RsErrorCode::DuplicateRecoveryShardIndex
+            };
+            return Err(RsError::new(code, format!("duplicate shard index {}", idx)));
+        }
+        seen[idx] = true;
+
+        // once there are enough shards, further recovery shards can't help
+        // (but originals still spare restoring them).
+        let surplus = added >= original_count && idx >= original_count;
+        if !surplus {
+            let data = shards.read_chunk(i, &mut scratch);
+            if idx < original_count {
+                decoder.add_original(idx, data)?;
+            } else {
+                decoder.add_recovery(idx - original_count, data)?;
+            }
+            added += 1;
+        }
+        on_added(i + 1)?;
+    }
+
+    Ok(())
+}
 
 fn rs_decode(
     original_count: usize,
     recovery_count: usize,
-    shards: RsShardsCollection,
-) -> Result<RsShardsCollection, Error> {
-    let mut decoder =
-        ReedSolomonDecoder::new(original_count, recovery_count, shards.shard_len.into())?;
-
-    for i in 0..shards.length {
-        let idx = shards.chunk_index_at(i) as usize;
-        let data = shards.chunk_at(i);
-        if idx < original_count {
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    decode_restored(original_count, recovery_count, shard_bytes, shards, false)
+}
+
+fn rs_try_decode(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<Option<RsShardsCollection>, RsError> {
+    match rs_decode(original_count, recovery_count, shard_bytes, shards) {
+        Ok(decoded) => Ok(Some(decoded)),
+        Err(err) if err.code() == RsErrorCode::NotEnoughShards => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn rs_decode_dedup(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    decode_restored(original_count, recovery_count, shard_bytes, shards, true)
+}
+
+fn rs_decode_split(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    originals: impl Shards,
+    recoveries: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    if originals.shard_len() != recoveries.shard_len() {
+        return Err(RsError::new(
+            RsErrorCode::DifferentShardSize,
+            format!(
+                "shard_len of originals ({}) and recoveries ({}) differ",
+                originals.shard_len(),
+                recoveries.shard_len()
+            ),
+        ));
+    }
+    // the indices must be validated up front, since they are offset when merged
+    check_indices_below(
+        &originals,
+        original_count,
+        RsErrorCode::InvalidOriginalShardIndex,
+    )?;
+    check_indices_below(
+        &recoveries,
+        recovery_count,
+        RsErrorCode::InvalidRecoveryShardIndex,
+    )?;
+
+    let shards = SplitShards {
+        original_count,
+        originals,
+        recoveries,
+    };
+    rs_decode(original_count, recovery_count, shard_bytes, shards)
+}
+
+/// Make sure all shard indices are lower than `count`.
+fn check_indices_below(
+    shards: &impl Shards,
+    count: usize,
+    code: RsErrorCode,
+) -> Result<(), RsError> {
+    for i in 0..shards.count() {
+        let idx = shards.index_at(i) as usize;
+        if idx >= count {
+            return Err(RsError::new(
+                code,
+                format!(
+                    "Shard index {} (at position {}) is out of range (expected less than {}).",
+                    idx, i, count
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Indices of shards (out of `total`) not marked as missing in `erasure_bitmap`.
+///
+/// See [`decode_with_erasures`] for the bitmap layout.
+fn present_indices(erasure_bitmap: &[u8], total: usize) -> Result<Vec<u16>, RsError> {
+    let expected = total.div_ceil(8);
+    if erasure_bitmap.len() < expected {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            format!(
+                "Erasure bitmap is too short ({} bytes) to cover {} shards ({} bytes).",
+                erasure_bitmap.len(),
+                total,
+                expected
+            ),
+        ));
+    }
+
+    Ok((0..total)
+        .filter(|idx| erasure_bitmap[idx / 8] & (1 << (idx % 8)) == 0)
+        .map(|idx| idx as u16)
+        .collect())
+}
+
+fn decode_restored(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    skip_duplicates: bool,
+) -> Result<RsShardsCollection, RsError> {
+    let mut decoder = prepare_decoder(
+        original_count,
+        recovery_count,
+        shard_bytes,
+        &shards,
+        skip_duplicates,
+    )?;
+    let decoding_result = decoder.decode()?;
+
+    Ok(restored_collection(
+        shards.shard_len(),
+        decoding_result.restored_original_iter(),
+    ))
+}
+
+fn rs_decode_sorted(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    original_shard_count: usize,
+) -> Result<RsShardsCollection, RsError> {
+    check_count("original_count", original_count)?;
+    validate_shard_bytes(shard_bytes)?;
+    check_shard_bytes(shard_bytes, &shards)?;
+    if original_shard_count > shards.count() {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            format!(
+                "original_shard_count ({}) exceeds the number of shards ({})",
+                original_shard_count,
+                shards.count()
+            ),
+        ));
+    }
+
+    let mut decoder = ReedSolomonDecoder::new(original_count, recovery_count, shard_bytes)?;
+
+    // strictly increasing indices within the right ranges are enough
+    // to rule out duplicates, so there is no need to track seen indices.
+    let mut next_min = 0;
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        let idx = shards.index_at(i) as usize;
+        let (begin, end) = if i < original_shard_count {
+            (0, original_count)
+        } else {
+            (original_count, original_count + recovery_count)
+        };
+        if idx < begin.max(next_min) || idx >= end {
+            return Err(RsError::new(
+                RsErrorCode::InvalidShardsCollection,
+                format!(
+                    "Shard index {} (at position {}) is out of order or out of range [{}, {}).",
+                    idx, i, begin, end
+                ),
+            ));
+        }
+        next_min = idx + 1;
+
+        let data = shards.read_chunk(i, &mut scratch);
+        if i < original_shard_count {
             decoder.add_original_shard(idx, data)?;
         } else {
             decoder.add_recovery_shard(idx - original_count, data)?;
         }
     }
+    let decoding_result = decoder.decode()?;
+
+    Ok(restored_collection(
+        shards.shard_len(),
+        decoding_result.restored_original_iter(),
+    ))
+}
+
+fn rs_decode_full(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<RsShardsCollection, RsError> {
+    decode_full_restored(original_count, recovery_count, shard_bytes, shards)
+        .map(|(decoded, _)| decoded)
+}
+
+/// Same as [`rs_decode_full`], but also returns the indices of restored originals.
+fn decode_full_restored(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<(RsShardsCollection, Vec<u16>), RsError> {
+    let mut data = Vec::with_capacity(original_count * shards.shard_len() as usize);
+    let restored = decode_full_with(
+        original_count,
+        recovery_count,
+        shard_bytes,
+        &shards,
+        |_, chunk| data.extend(chunk),
+    )?;
+
+    let decoded = RsShardsCollection {
+        length: original_count,
+        shard_len: shards.shard_len(),
+        data,
+        indices: Some((0..original_count as u16).collect()),
+    };
+    Ok((decoded, restored))
+}
+
+fn rs_decode_full_into(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    output_len: usize,
+    write: impl FnMut(usize, &[u8]),
+) -> Result<usize, RsError> {
+    let required = shards_len(original_count, shard_bytes, RsErrorCode::OutOfBounds)?;
+    if output_len < required {
+        return Err(RsError::new(
+            RsErrorCode::OutOfBounds,
+            format!(
+                "Output buffer is too small: got {} bytes, need {}.",
+                output_len, required
+            ),
+        ));
+    }
+
+    decode_full_with(original_count, recovery_count, shard_bytes, shards, write)?;
+
+    Ok(required)
+}
+
+/// Restore all originals and pass them (in index order) to `write`,
+/// together with the offset of each shard in the concatenated output.
+///
+/// Returns the indices of the restored originals.
+fn decode_full_with(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    mut write: impl FnMut(usize, &[u8]),
+) -> Result<Vec<u16>, RsError> {
+    let mut decoder = prepare_decoder(original_count, recovery_count, shard_bytes, &shards, false)?;
+    let decoding_result = decoder.decode()?;
+
+    // position of every original shard within the input collection (if present)
+    let mut present = vec![None; original_count];
+    for i in 0..shards.count() {
+        let idx = shards.index_at(i) as usize;
+        if idx < original_count {
+            present[idx] = Some(i);
+        }
+    }
+
+    let mut restored = vec![];
+    let mut scratch = vec![];
+    for (idx, position) in present.into_iter().enumerate() {
+        let chunk = match position {
+            Some(position) => shards.read_chunk(position, &mut scratch),
+            None => {
+                restored.push(idx as u16);
+                decoding_result
+                    .restored_original(idx)
+                    .expect("Missing originals are restored by the decoder.")
+            }
+        };
+        write(idx * shard_bytes, chunk);
+    }
+
+    Ok(restored)
+}
+
+fn rs_repair_original(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    wanted_index: u16,
+    shards: impl Shards,
+) -> Result<Vec<u8>, RsError> {
+    let wanted = wanted_index as usize;
+    if wanted >= original_count {
+        return Err(RsError::new(
+            RsErrorCode::InvalidOriginalShardIndex,
+            format!(
+                "wanted original shard index ({}) must be lower than original_count ({})",
+                wanted, original_count
+            ),
+        ));
+    }
+    if (0..shards.count()).any(|i| shards.index_at(i) == wanted_index) {
+        return Err(RsError::new(
+            RsErrorCode::InvalidOriginalShardIndex,
+            format!("original shard {} is present, nothing to repair", wanted),
+        ));
+    }
+
+    let mut decoder = prepare_decoder(original_count, recovery_count, shard_bytes, &shards, false)?;
+    let decoding_result = decoder.decode()?;
+    let restored = decoding_result
+        .restored_original(wanted)
+        .expect("Missing originals are restored by the decoder.");
+
+    Ok(restored.to_vec())
+}
+
+/// Gather original shards restored by the decoder into a collection.
+fn restored_collection<'a>(
+    shard_len: u32,
+    restored: impl Iterator<Item = (usize, &'a [u8])>,
+) -> RsShardsCollection {
+    let mut indices = vec![];
+    let mut data = vec![];
+    for (idx, shard) in restored {
+        indices.push(idx as u16);
+        data.extend(shard);
+    }
+
+    RsShardsCollection {
+        length: indices.len(),
+        shard_len,
+        indices: Some(indices),
+        data,
+    }
+}
+
+/// Initialize the library.
+///
+/// Should be called once at startup. When built with the `console_error_panic_hook`
+/// feature, it makes panics log a readable message to the console
+/// (instead of a generic `unreachable` trap). Calling it again has no effect.
+#[wasm_bindgen]
+pub fn init() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+/// Version of the underlying `reed_solomon` crate (pinned in `Cargo.toml`).
+const REED_SOLOMON_VERSION: &str = "2.2.1";
+
+/// Version of this library and of the underlying `reed_solomon` crate.
+///
+/// Worth including in bug reports, e.g. `reed-solomon-wasm 0.1.0 (reed-solomon-simd 2.2.1)`.
+#[wasm_bindgen]
+pub fn version() -> String {
+    format!(
+        "{} {} (reed-solomon-simd {})",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        REED_SOLOMON_VERSION
+    )
+}
+
+/// Compute `recovery_count` recovery shards for given original `shards`.
+///
+/// `shard_bytes` must match the `shard_len` of the collection.
+///
+/// Recovery shards in the result carry absolute indices, i.e. they start
+/// at `original_count` (the number of input shards), so the result can be
+/// passed to [`decode`] as-is.
+#[wasm_bindgen]
+pub fn encode(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_encode(recovery_count as usize, shard_bytes as usize, shards)?;
+
+    Ok(result.into())
+}
+
+/// Compute recovery shards for each of the `frames` (collections of original shards).
+///
+/// Same as calling [`encode`] for every frame, but one encoder is re-used for all of them,
+/// which is cheaper for a series of equally-sized frames.
+/// The result contains recovery shards of every frame, in order.
+#[wasm_bindgen]
+pub fn encode_many(
+    recovery_count: u16,
+    shard_bytes: u32,
+    frames: Vec<ShardsCollection>,
+) -> Result<Vec<ShardsCollection>, RsError> {
+    let results = rs_encode_many(recovery_count as usize, shard_bytes as usize, frames)?;
+
+    Ok(results.into_iter().map(Into::into).collect())
+}
+
+/// Compute recovery shards and return them together with the original `shards`.
+///
+/// The result contains all `original_count + recovery_count` shards in index order:
+/// the original shards followed by the recovery shards.
+/// The parameters are recorded in the result (see `ShardsCollection::config`).
+#[wasm_bindgen]
+pub fn encode_systematic(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let original_count = shards.length as u16;
+    let result =
+        rs_encode_systematic(recovery_count as usize, shard_bytes as usize, shards.into())?;
+
+    let mut result: ShardsCollection = result.into();
+    result.config = Some(CodingConfig {
+        original_count,
+        recovery_count,
+        shard_bytes,
+    });
+
+    Ok(result)
+}
+
+/// Compute recovery shards and write them into caller-provided `output`.
+///
+/// The `output` must be at least `recovery_count * shard_bytes` long.
+/// Recovery shards are written in order, starting at offset `0`.
+/// Returns the number of bytes written.
+#[wasm_bindgen]
+pub fn encode_into(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    output: js_sys::Uint8Array,
+) -> Result<u32, RsError> {
+    let written = rs_encode_into(
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        output.length() as usize,
+        |offset, chunk| {
+            let offset = offset as u32;
+            output
+                .subarray(offset, offset + chunk.len() as u32)
+                .copy_from(chunk);
+        },
+    )?;
+
+    Ok(written as u32)
+}
+
+/// Compute only selected recovery shards.
+///
+/// `wanted` contains relative recovery indices (each lower than `recovery_count`),
+/// the result contains these shards (in the `wanted` order) labelled with
+/// their absolute indices, just like shards returned by [`encode`].
+#[wasm_bindgen]
+pub fn encode_subset(
+    recovery_count: u16,
+    shard_bytes: u32,
+    wanted: js_sys::Uint16Array,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_encode_subset(
+        recovery_count as usize,
+        shard_bytes as usize,
+        &wanted.to_vec(),
+        shards,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Re-compute a single (e.g. lost) recovery shard from all original `shards`.
+///
+/// `target_recovery_index` is relative (must be lower than `recovery_count`).
+/// The result is the data of the shard, the same as the one returned by [`encode`].
+#[wasm_bindgen]
+pub fn regenerate_recovery(
+    recovery_count: u16,
+    shard_bytes: u32,
+    target_recovery_index: u16,
+    shards: ShardsCollection,
+) -> Result<js_sys::Uint8Array, RsError> {
+    let result = rs_regenerate_recovery(
+        recovery_count as usize,
+        shard_bytes as usize,
+        target_recovery_index,
+        shards,
+    )?;
+
+    Ok(result.as_slice().into())
+}
+
+/// Check whether `candidate` is exactly the recovery shard at `recovery_index`
+/// that [`encode`] would produce from the original `shards`.
+///
+/// `recovery_index` is relative (must be lower than `recovery_count`).
+/// Handy to pinpoint where a recovery shard got corrupted, while the originals are still around.
+#[wasm_bindgen]
+pub fn verify_recovery(
+    recovery_count: u16,
+    shard_bytes: u32,
+    recovery_index: u16,
+    shards: ShardsCollection,
+    candidate: js_sys::Uint8Array,
+) -> Result<bool, RsError> {
+    rs_verify_recovery(
+        recovery_count as usize,
+        shard_bytes as usize,
+        recovery_index,
+        shards,
+        &candidate.to_vec(),
+    )
+}
+
+/// Restore missing original shards.
+///
+/// Shards with index lower than `original_count` are treated as originals,
+/// the rest are recovery shards (as returned by [`encode`]).
+/// The result contains only the restored original shards.
+/// Passing more shards than needed is fine: surplus recovery shards
+/// (once `original_count` shards were seen) are simply skipped.
+#[wasm_bindgen]
+pub fn decode(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_decode(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Same as [`decode`], but returns `null` instead of throwing if there are not enough shards.
+///
+/// Other errors (e.g. a wrong shard length or an out-of-range index) are
+/// most likely mistakes of the caller, so they are still thrown.
+#[wasm_bindgen]
+pub fn try_decode(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<Option<ShardsCollection>, RsError> {
+    let result = rs_try_decode(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    Ok(result.map(Into::into))
+}
+
+/// Same as [`decode`], but shards with duplicated indices are ignored
+/// (only the first occurrence is used) instead of being rejected.
+#[wasm_bindgen]
+pub fn decode_dedup(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_decode_dedup(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Same as [`decode`], but original and recovery shards are passed separately.
+///
+/// Indices of `originals` are in `0..original_count` and indices of `recoveries`
+/// are relative, i.e. in `0..recovery_count` (the first recovery shard has index `0`).
+#[wasm_bindgen]
+pub fn decode_split(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    originals: ShardsCollection,
+    recoveries: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_decode_split(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        originals,
+        recoveries,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Same as [`decode`], but the shards must be sorted by index: the first
+/// `original_shard_count` shards are originals and the rest are recovery shards.
+///
+/// The order is verified on the way, which is cheaper than the general
+/// routing done by [`decode`]. Fails if the shards are not sorted.
+#[wasm_bindgen]
+pub fn decode_sorted(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    original_shard_count: usize,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_decode_sorted(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        original_shard_count,
+    )?;
+
+    Ok(result.into())
+}
+
+/// Same as [`decode`], but the missing shards are given as a bitmap instead of indices.
+///
+/// Bit `i % 8` (least significant first) of byte `i / 8` of `erasure_bitmap` is set
+/// if the shard with (absolute) index `i` is missing, so the bitmap covers
+/// `original_count + recovery_count` shards (trailing bits are ignored).
+/// `present_data` contains all of the present shards, concatenated in index order.
+#[wasm_bindgen]
+pub fn decode_with_erasures(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    present_data: js_sys::Uint8Array,
+    erasure_bitmap: js_sys::Uint8Array,
+) -> Result<ShardsCollection, RsError> {
+    let indices = present_indices(
+        &erasure_bitmap.to_vec(),
+        original_count as usize + recovery_count as usize,
+    )?;
+    let shards = ShardsCollection::new(
+        shard_bytes,
+        present_data,
+        Some(indices.as_slice().into()),
+        None,
+    )?;
+
+    decode(original_count, recovery_count, shard_bytes, shards)
+}
+
+/// Same as [`decode`], but the parameters are taken from the collection's `config`.
+///
+/// Works for collections produced by `encode_systematic` (or derived from them,
+/// e.g. with `slice`). Fails if the collection has no config.
+#[wasm_bindgen]
+pub fn decode_auto(shards: ShardsCollection) -> Result<ShardsCollection, RsError> {
+    let Some(config) = shards.config else {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            "The collection has no config, use `decode` with explicit parameters.",
+        ));
+    };
+
+    decode(
+        config.original_count,
+        config.recovery_count,
+        config.shard_bytes,
+        shards,
+    )
+}
+
+/// Restore all original shards.
+///
+/// Contrary to [`decode`] the result contains all `original_count` shards
+/// in index order (shards present in the input are copied over),
+/// densely laid out with explicit indices `0..original_count`,
+/// so `chunk_at(i)` returns original shard `i` and `data` is the whole file.
+/// The indices of shards which had to be restored are available as `restored_indices`.
+#[wasm_bindgen]
+pub fn decode_full(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let (result, restored) = decode_full_restored(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    let mut result: ShardsCollection = result.into();
+    result.restored = Some(restored);
+
+    Ok(result)
+}
+
+/// Restore all original shards and write them into caller-provided `output`.
+///
+/// Same as [`decode_full`], but the original shards are written in index order
+/// starting at offset `0`. The `output` must be at least `original_count * shard_bytes` long.
+/// Returns the number of bytes written.
+#[wasm_bindgen]
+pub fn decode_full_into(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    output: js_sys::Uint8Array,
+) -> Result<u32, RsError> {
+    let written = rs_decode_full_into(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        output.length() as usize,
+        |offset, chunk| {
+            let offset = offset as u32;
+            output
+                .subarray(offset, offset + chunk.len() as u32)
+                .copy_from(chunk);
+        },
+    )?;
+
+    Ok(written as u32)
+}
+
+/// Restore a single missing original shard.
+///
+/// Only the shard with `wanted_index` is returned (as plain data),
+/// which is handy to repair one lost block of a large file.
+/// Fails if the shard is present in `shards` or there are not enough shards to restore it.
+#[wasm_bindgen]
+pub fn repair_original(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    wanted_index: u16,
+    shards: ShardsCollection,
+) -> Result<js_sys::Uint8Array, RsError> {
+    let result = rs_repair_original(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        wanted_index,
+        shards,
+    )?;
+
+    Ok(result.as_slice().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const SHARD: usize = 64;
+
+    fn test_data_originals() -> RsShardsCollection {
+        let mut data = vec![];
+        data.extend([1u8; SHARD]);
+        data.extend([2u8; SHARD]);
+        data.extend([3u8; SHARD]);
+
+        RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data,
+            indices: None,
+        }
+    }
+
+    fn test_data(recovery_count: usize) -> RsShardsCollection {
+        rs_encode(recovery_count, SHARD, test_data_originals()).unwrap()
+    }
+
+    #[test]
+    fn should_encode_shards() {
+        let recovery_count = 5;
+        let encoded = test_data(recovery_count);
+
+        assert_eq!(encoded.length, recovery_count);
+        assert_eq!(encoded.shard_len, SHARD as u32);
+        assert_eq!(encoded.chunk_index_at(0), 3);
+        assert_eq!(encoded.chunk_index_at(1), 4);
+        assert_eq!(encoded.chunk_index_at(2), 5);
+        assert_eq!(encoded.chunk_index_at(3), 6);
+        assert_eq!(encoded.chunk_index_at(4), 7);
+        assert_eq!(
+            encoded.chunk_at(0),
+            &[
+                156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156,
+                156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 15,
+                15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+                15, 15, 15, 15, 15, 15, 15, 15, 15, 15
+            ]
+        );
+        assert_eq!(
+            encoded.chunk_at(1),
+            &[
+                159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159,
+                159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 12,
+                12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12,
+                12, 12, 12, 12, 12, 12, 12, 12, 12, 12
+            ]
+        );
+        assert_eq!(
+            encoded.chunk_at(2),
+            &[
+                158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158,
+                158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 13,
+                13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13,
+                13, 13, 13, 13, 13, 13, 13, 13, 13, 13
+            ]
+        );
+        assert_eq!(
+            encoded.chunk_at(3),
+            &[
+                157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157,
+                157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 14,
+                14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14,
+                14, 14, 14, 14, 14, 14, 14, 14, 14, 14
+            ]
+        );
+        assert_eq!(
+            encoded.chunk_at(4),
+            &[
+                175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175,
+                175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 8,
+                8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8,
+                8, 8, 8
+            ]
+        );
+    }
+
+    #[test]
+    fn should_label_recovery_shards_after_originals() {
+        let shards = RsShardsCollection {
+            length: 5,
+            shard_len: SHARD as u32,
+            data: vec![9u8; 5 * SHARD],
+            indices: None,
+        };
+
+        let encoded = rs_encode(3, SHARD, shards).unwrap();
+
+        assert_eq!(encoded.indices, Some(vec![5, 6, 7]));
+    }
+
+    #[test]
+    fn should_encode_systematic() {
+        let recovery = test_data(2);
+
+        let encoded = rs_encode_systematic(2, SHARD, test_data_originals()).unwrap();
+
+        assert_eq!(encoded.length, 5);
+        assert_eq!(encoded.indices, Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(&encoded.data[..3 * SHARD], &test_data_originals().data[..]);
+        assert_eq!(&encoded.data[3 * SHARD..], &recovery.data[..]);
+    }
+
+    #[test]
+    fn should_encode_into_output_buffer() {
+        let expected = test_data(3);
+        let mut output = vec![0u8; 4 * SHARD];
+
+        let written = rs_encode_into(3, SHARD, test_data_originals(), output.len(), |o, c| {
+            output[o..o + c.len()].copy_from_slice(c)
+        })
+        .unwrap();
+
+        assert_eq!(written, 3 * SHARD);
+        assert_eq!(&output[..written], &expected.data[..]);
+        assert_eq!(&output[written..], &[0u8; SHARD]);
+
+        let err =
+            rs_encode_into(3, SHARD, test_data_originals(), 2 * SHARD, |_, _| {}).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::OutOfBounds);
+    }
+
+    #[test]
+    fn should_decode_shards() {
+        let recovery_count = 6;
+        let encoded = test_data(recovery_count);
+
+        let to_decode = RsShardsCollection {
+            length: 3,
+            shard_len: encoded.shard_len,
+            data: {
+                let mut data = vec![];
+                data.extend(encoded.chunk_at(0));
+                data.extend(&[2u8; SHARD]);
+                data.extend(encoded.chunk_at(4));
+                data
+            },
+            indices: vec![encoded.chunk_index_at(0), 1, encoded.chunk_index_at(4)].into(),
+        };
+
+        let decoded = rs_decode(3, recovery_count, SHARD, to_decode).unwrap();
+
+        assert_eq!(decoded.length, 2);
+        assert_eq!(decoded.shard_len, encoded.shard_len);
+        assert_eq!(decoded.chunk_index_at(0), 0u16);
+        assert_eq!(decoded.chunk_index_at(1), 2u16);
+        assert_eq!(decoded.chunk_at(0), &[1u8; SHARD]);
+        assert_eq!(decoded.chunk_at(1), &[3u8; SHARD]);
+    }
+
+    #[test]
+    fn should_report_restored_shards_count() {
+        let recovery_count = 4;
+        let encoded = test_data(recovery_count);
+
+        // drop originals 0 and 2
+        let to_decode = RsShardsCollection {
+            length: 3,
+            shard_len: encoded.shard_len,
+            data: {
+                let mut data = vec![];
+                data.extend(&[2u8; SHARD]);
+                data.extend(encoded.chunk_at(1));
+                data.extend(encoded.chunk_at(3));
+                data
+            },
+            indices: vec![1, encoded.chunk_index_at(1), encoded.chunk_index_at(3)].into(),
+        };
+
+        let decoded = rs_decode(3, recovery_count, SHARD, to_decode).unwrap();
+
+        assert_eq!(decoded.length, 2);
+        assert_eq!(decoded.data.len(), 2 * SHARD);
+        assert_eq!(decoded.chunk_index_at(0), 0u16);
+        assert_eq!(decoded.chunk_at(0), &[1u8; SHARD]);
+        assert_eq!(decoded.chunk_index_at(1), 2u16);
+        assert_eq!(decoded.chunk_at(1), &[3u8; SHARD]);
+    }
+
+    #[test]
+    fn should_decode_from_recovery_shards_only() {
+        let recovery_count = 3;
+        let encoded = test_data(recovery_count);
+
+        let decoded = rs_decode(3, recovery_count, SHARD, encoded).unwrap();
+
+        assert_eq!(decoded.length, 3);
+        for i in 0..3 {
+            assert_eq!(decoded.chunk_index_at(i), i as u16);
+            assert_eq!(decoded.chunk_at(i), &[i as u8 + 1; SHARD]);
+        }
+    }
+
+    #[test]
+    fn should_decode_with_more_recovery_than_original_shards() {
+        let originals = RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data: (0..4 * SHARD).map(|i| (i % 199) as u8).collect(),
+            indices: None,
+        };
+        let encoded = rs_encode(32, SHARD, &originals).unwrap();
+        assert_eq!(encoded.length, 32);
+
+        let mut data = vec![];
+        let mut indices = vec![];
+        for position in [31, 3, 17, 8] {
+            data.extend(encoded.chunk_at(position));
+            indices.push(encoded.chunk_index_at(position));
+        }
+        let recovery_only = RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data,
+            indices: Some(indices),
+        };
+
+        let decoded = rs_decode_full(4, 32, SHARD, recovery_only).unwrap();
+
+        assert_eq!(decoded.data, originals.data);
+    }
+
+    #[test]
+    fn should_return_error_code() {
+        let encoded = test_data(2);
+
+        let err = rs_decode(3, 2, SHARD, encoded).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+        assert_eq!(err.message(), err.to_string());
+    }
+
+    #[test]
+    fn should_report_shard_counts_when_not_enough_shards() {
+        let encoded = test_data(3);
+
+        let two = RsShardsCollection {
+            length: 2,
+            shard_len: SHARD as u32,
+            data: encoded.data[SHARD..].to_vec(),
+            indices: Some(vec![4, 5]),
+        };
+        let err = rs_decode(3, 3, SHARD, two).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+        assert_eq!(err.have(), Some(2));
+        assert_eq!(err.need(), Some(3));
+
+        let err = rs_decode(3, 3, SHARD * 2, encoded).unwrap_err();
+        assert_eq!(err.have(), None);
+    }
+
+    #[test]
+    fn should_reject_mismatching_shard_bytes() {
+        let encoded = test_data(2);
+
+        let err = rs_decode(3, 2, SHARD * 2, encoded).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::DifferentShardSize);
+        assert_eq!(
+            err.message(),
+            "shard_bytes (128) does not match shard_len (64)"
+        );
+    }
+
+    #[test]
+    fn should_reject_out_of_range_shard_index() {
+        let to_decode = RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data: vec![0u8; 3 * SHARD],
+            indices: vec![0, 65535, 1].into(),
+        };
+
+        let err = rs_decode(3, 2, SHARD, to_decode).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+        assert_eq!(
+            err.message(),
+            "Shard index 65535 (at position 1) is out of range (original_count + recovery_count = 5)."
+        );
+    }
+
+    #[test]
+    fn should_try_decode_with_not_enough_shards() {
+        let encoded = test_data(2);
+        let originals = test_data_originals();
+        let mut to_decode = RsShardsCollection {
+            length: 2,
+            shard_len: SHARD as u32,
+            data: [originals.chunk_at(1), encoded.chunk_at(0)].concat(),
+            indices: Some(vec![1, 3]),
+        };
+
+        assert!(rs_try_decode(3, 2, SHARD, &to_decode).unwrap().is_none());
+
+        to_decode.data.extend(encoded.chunk_at(1));
+        to_decode.indices = Some(vec![1, 3, 4]);
+        to_decode.length = 3;
+        let decoded = rs_try_decode(3, 2, SHARD, &to_decode).unwrap().unwrap();
+        let expected = rs_decode(3, 2, SHARD, &to_decode).unwrap();
+        assert_eq!(decoded.indices, expected.indices);
+        assert_eq!(decoded.data, expected.data);
+
+        let err = rs_try_decode(3, 2, SHARD + 2, &to_decode).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::DifferentShardSize);
+    }
+
+    #[test]
+    fn should_not_overflow_total_shards_len() {
+        assert_eq!(
+            shards_len(3, SHARD, RsErrorCode::OutOfBounds),
+            Ok(3 * SHARD)
+        );
+
+        let err = shards_len(usize::MAX / 2 + 1, 2, RsErrorCode::OutOfBounds).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::OutOfBounds);
+
+        // wraps to a small number if computed with (32-bit) `usize` on wasm32
+        let (count, shard_bytes) = (u16::MAX as usize, limits::MAX_SHARD_BYTES);
+        let huge = rs_encode_into(count, shard_bytes, test_data_originals(), 4096, |_, _| {});
+        assert_eq!(huge.unwrap_err().code(), RsErrorCode::OutOfBounds);
+        let huge = rs_decode_full_into(
+            count,
+            2,
+            shard_bytes,
+            test_data_originals(),
+            4096,
+            |_, _| {},
+        );
+        assert_eq!(huge.unwrap_err().code(), RsErrorCode::OutOfBounds);
+    }
+
+    #[test]
+    fn should_check_index_range() {
+        assert_eq!(check_index_in_range(4, 0, 5), Ok(()));
+
+        let err = check_index_in_range(5, 2, 5).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+        assert_eq!(
+            err.message(),
+            "Shard index 5 (at position 2) is out of range (original_count + recovery_count = 5)."
+        );
+    }
+
+    fn with_duplicates(encoded: &RsShardsCollection) -> RsShardsCollection {
+        let mut data = vec![];
+        data.extend(encoded.chunk_at(0));
+        data.extend(&[2u8; SHARD]);
+        data.extend(&[0u8; SHARD]);
+        data.extend(encoded.chunk_at(2));
+
+        RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data,
+            indices: vec![encoded.chunk_index_at(0), 1, 1, encoded.chunk_index_at(2)].into(),
+        }
+    }
+
+    #[test]
+    fn should_reject_duplicate_shard_index() {
+        let encoded = test_data(3);
+
+        let err = rs_decode(3, 3, SHARD, with_duplicates(&encoded)).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::DuplicateOriginalShardIndex);
+        assert_eq!(err.message(), "duplicate shard index 1");
+    }
+
+    #[test]
+    fn should_skip_duplicate_shard_index() {
+        let encoded = test_data(3);
+
+        let decoded = rs_decode_dedup(3, 3, SHARD, with_duplicates(&encoded)).unwrap();
+
+        assert_eq!(decoded.length, 2);
+        assert_eq!(decoded.chunk_at(0), &[1u8; SHARD]);
+        assert_eq!(decoded.chunk_at(1), &[3u8; SHARD]);
+    }
+
+    #[test]
+    fn should_validate_shard_bytes_up_front() {
+        let odd = RsShardsCollection {
+            length: 3,
+            shard_len: 63,
+            data: vec![1u8; 3 * 63],
+            indices: None,
+        };
+
+        let err = rs_encode(2, 63, &odd).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardSize);
+        assert_eq!(err.message(), "shard_bytes must be a multiple of 2");
+        let err = rs_decode(3, 2, 63, &odd).unwrap_err();
+        assert_eq!(err.message(), "shard_bytes must be a multiple of 2");
+
+        let too_large = limits::MAX_SHARD_BYTES + 2;
+        let expected = format!(
+            "shard_bytes exceeds maximum ({} > {})",
+            too_large,
+            limits::MAX_SHARD_BYTES
+        );
+        let err = rs_encode(2, too_large, test_data_originals()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardSize);
+        assert_eq!(err.message(), expected);
+        let err = rs_decode(3, 2, too_large, test_data(2)).unwrap_err();
+        assert_eq!(err.message(), expected);
+
+        assert_eq!(validate_shard_bytes(SHARD), Ok(()));
+        assert_eq!(
+            validate_shard_bytes(0).unwrap_err().message(),
+            "shard_bytes must be greater than 0"
+        );
+    }
+
+    #[test]
+    fn should_skip_surplus_shards() {
+        let encoded = test_data(8);
+        let minimal = RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data: [&[2u8; SHARD][..], encoded.chunk_at(5), encoded.chunk_at(1)].concat(),
+            indices: Some(vec![1, 8, 4]),
+        };
+        // 6 more shards than needed, with an original at the very end
+        let mut surplus = RsShardsCollection {
+            length: 9,
+            shard_len: SHARD as u32,
+            data: minimal.data.clone(),
+            indices: minimal.indices.clone(),
+        };
+        for i in [0, 2, 3, 6, 7] {
+            surplus.data.extend(encoded.chunk_at(i));
+            surplus.indices.as_mut().unwrap().push(3 + i as u16);
+        }
+        surplus.data.extend([3u8; SHARD]);
+        surplus.indices.as_mut().unwrap().push(2);
+
+        let expected = rs_decode(3, 8, SHARD, &minimal).unwrap();
+        assert_eq!(expected.indices, Some(vec![0, 2]));
+
+        let decoded = rs_decode(3, 8, SHARD, &surplus).unwrap();
+        assert_eq!(decoded.indices, Some(vec![0]));
+        assert_eq!(decoded.chunk_at(0), expected.chunk_at(0));
+        let decoded = rs_decode_full(3, 8, SHARD, &surplus).unwrap();
+        assert_eq!(decoded.data, test_data_originals().data);
+
+        // surplus shards are still validated
+        surplus.indices.as_mut().unwrap()[5] = 8;
+        let err = rs_decode(3, 8, SHARD, &surplus).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::DuplicateRecoveryShardIndex);
+    }
+
+    #[test]
+    fn should_decode_sorted_shards_like_decode() {
+        let encoded = test_data(4);
+        let sorted = RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data: [&[2u8; SHARD][..], encoded.chunk_at(0), encoded.chunk_at(3)].concat(),
+            indices: Some(vec![1, 3, 6]),
+        };
+
+        let expected = rs_decode(3, 4, SHARD, &sorted).unwrap();
+        let decoded = rs_decode_sorted(3, 4, SHARD, &sorted, 1).unwrap();
+
+        assert_eq!(decoded.indices, expected.indices);
+        assert_eq!(decoded.data, expected.data);
+        assert_eq!(decoded.indices, Some(vec![0, 2]));
+
+        // the recoveries are not sorted
+        let mut unsorted = sorted;
+        unsorted.indices = Some(vec![1, 6, 3]);
+        let err = rs_decode_sorted(3, 4, SHARD, &unsorted, 1).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        // wrong partition point
+        let err = rs_decode_sorted(3, 4, SHARD, &unsorted, 2).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        let err = rs_decode_sorted(3, 4, SHARD, &unsorted, 4).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[test]
+    fn should_decode_split_collections() {
+        let encoded = test_data(3);
+        let originals = RsShardsCollection {
+            length: 1,
+            shard_len: SHARD as u32,
+            data: vec![2u8; SHARD],
+            indices: Some(vec![1]),
+        };
+        let recoveries = RsShardsCollection {
+            length: 2,
+            shard_len: SHARD as u32,
+            data: [encoded.chunk_at(2), encoded.chunk_at(0)].concat(),
+            indices: Some(vec![2, 0]),
+        };
+
+        let decoded = rs_decode_split(3, 3, SHARD, &originals, &recoveries).unwrap();
+
+        assert_eq!(decoded.length, 2);
+        assert_eq!(decoded.indices, Some(vec![0, 2]));
+        assert_eq!(decoded.chunk_at(0), &[1u8; SHARD]);
+        assert_eq!(decoded.chunk_at(1), &[3u8; SHARD]);
+
+        // recovery indices are relative, so `3` is out of range
+        let mut shifted = recoveries;
+        shifted.indices = Some(vec![2, 3]);
+        let err = rs_decode_split(3, 3, SHARD, &originals, &shifted).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+
+        let mut misplaced = originals;
+        misplaced.indices = Some(vec![4]);
+        let err = rs_decode_split(3, 3, SHARD, misplaced, &shifted).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidOriginalShardIndex);
+    }
+
+    #[test]
+    fn should_validate_collection_layout() {
+        assert_eq!(shards_count(64, 192, None), Ok(3));
+        assert_eq!(shards_count(64, 192, Some(3)), Ok(3));
+
+        let err = shards_count(64, 100, None).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(
+            err.message(),
+            "data length (100) is not a multiple of shard_len (64)"
+        );
+
+        let err = shards_count(0, 100, None).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+
+        let err = shards_count(64, 192, Some(2)).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[test]
+    fn should_check_explicit_shards_count() {
+        assert_eq!(check_shards_count(64, 3, 192), Ok(()));
+        assert_eq!(check_shards_count(64, 0, 0), Ok(()));
+
+        let err = check_shards_count(64, 3, 191).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(
+            err.message(),
+            "Expected 3 shards of 64 bytes (192 bytes), got 191 bytes."
+        );
+
+        // a valid collection, but not the one the caller intended
+        let err = check_shards_count(64, 3, 256).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[test]
+    fn should_check_collection_invariants() {
+        assert_eq!(check_collection(64, 3, 192, None), Ok(()));
+        assert_eq!(check_collection(64, 3, 192, Some(&[5, 0, 1])), Ok(()));
+
+        let not_multiple = check_collection(64, 3, 190, None).unwrap_err();
+        assert_eq!(not_multiple.code(), RsErrorCode::InvalidShardsCollection);
+
+        let wrong_length = check_collection(64, 2, 192, None).unwrap_err();
+        assert_eq!(wrong_length.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(
+            wrong_length.message(),
+            "length (2) does not match the number of shards in data (3)"
+        );
+
+        let wrong_indices = check_collection(64, 3, 192, Some(&[0, 1])).unwrap_err();
+        assert_eq!(wrong_indices.code(), RsErrorCode::InvalidShardsCollection);
+
+        let duplicates = check_collection(64, 3, 192, Some(&[0, 4, 4])).unwrap_err();
+        assert_eq!(duplicates.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(duplicates.message(), "duplicate shard index 4");
+    }
+
+    #[test]
+    fn should_encode_many_frames() {
+        let frames: Vec<_> = [1u8, 10, 100]
+            .into_iter()
+            .map(|seed| RsShardsCollection {
+                length: 3,
+                shard_len: SHARD as u32,
+                data: (0..3 * SHARD).map(|i| seed.wrapping_add(i as u8)).collect(),
+                indices: None,
+            })
+            .collect();
+
+        let encoded = rs_encode_many(4, SHARD, &frames).unwrap();
+
+        assert_eq!(encoded.len(), 3);
+        for (encoded, frame) in encoded.iter().zip(&frames) {
+            let expected = rs_encode(4, SHARD, frame).unwrap();
+            assert_eq!(encoded.indices, expected.indices);
+            assert_eq!(encoded.data, expected.data);
+        }
+    }
+
+    #[test]
+    fn should_reject_zero_shard_counts() {
+        let err = rs_encode(0, SHARD, test_data_originals()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::UnsupportedShardCount);
+        assert_eq!(err.message(), "recovery_count must be at least 1");
+
+        let err = rs_decode(0, 2, SHARD, test_data(2)).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::UnsupportedShardCount);
+        assert_eq!(err.message(), "original_count must be at least 1");
+    }
+
+    #[test]
+    fn should_reject_empty_collection() {
+        let empty = || RsShardsCollection {
+            length: 0,
+            shard_len: SHARD as u32,
+            data: vec![],
+            indices: None,
+        };
+
+        let err = rs_encode(2, SHARD, empty()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(err.message(), "no shards provided");
+
+        let err = rs_decode(3, 2, SHARD, empty()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+    }
+
+    #[test]
+    fn should_reject_original_shards_out_of_order() {
+        let mut shards = test_data_originals();
+        shards.indices = Some(vec![0, 2, 1]);
+
+        let err = rs_encode(2, SHARD, &shards).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(
+            err.message(),
+            "Original shard at position 1 has index 2, input shards must be in order."
+        );
+
+        shards.indices = Some(vec![0, 1, 2]);
+        assert!(rs_encode(2, SHARD, &shards).is_ok());
+    }
+
+    #[test]
+    fn should_encode_subset_of_recovery_shards() {
+        let full = test_data(6);
+        let subset = rs_encode_subset(6, SHARD, &[4, 1], test_data_originals()).unwrap();
+
+        assert_eq!(subset.length, 2);
+        assert_eq!(subset.chunk_index_at(0), full.chunk_index_at(4));
+        assert_eq!(subset.chunk_at(0), full.chunk_at(4));
+        assert_eq!(subset.chunk_index_at(1), full.chunk_index_at(1));
+        assert_eq!(subset.chunk_at(1), full.chunk_at(1));
+
+        let err = rs_encode_subset(6, SHARD, &[6], test_data_originals()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+    }
+
+    #[test]
+    fn should_repair_single_original_shard() {
+        let encoded = test_data(2);
+        let mut data = vec![];
+        data.extend([1u8; SHARD]);
+        data.extend([2u8; SHARD]);
+        data.extend(encoded.chunk_at(1));
+        let shards = || RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data: data.clone(),
+            indices: Some(vec![0, 1, 4]),
+        };
+
+        let repaired = rs_repair_original(3, 2, SHARD, 2, shards()).unwrap();
+        assert_eq!(repaired, vec![3u8; SHARD]);
+
+        let err = rs_repair_original(3, 2, SHARD, 1, shards()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidOriginalShardIndex);
+
+        let mut too_few = shards();
+        too_few.length = 2;
+        too_few.data.truncate(2 * SHARD);
+        too_few.indices = Some(vec![0, 1]);
+        let err = rs_repair_original(3, 2, SHARD, 2, too_few).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+    }
+
+    #[test]
+    fn should_regenerate_single_recovery_shard() {
+        let full = test_data(6);
+
+        for idx in 0..6 {
+            let shard = rs_regenerate_recovery(6, SHARD, idx, test_data_originals()).unwrap();
+            assert_eq!(shard, full.chunk_at(idx as usize));
+        }
+
+        let err = rs_regenerate_recovery(6, SHARD, 6, test_data_originals()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+    }
+
+    #[test]
+    fn should_verify_recovery_shard() {
+        let encoded = test_data(4);
+        let mut candidate = encoded.chunk_at(2).to_vec();
+
+        assert_eq!(
+            rs_verify_recovery(4, SHARD, 2, test_data_originals(), &candidate),
+            Ok(true)
+        );
+        // right data, wrong index
+        assert_eq!(
+            rs_verify_recovery(4, SHARD, 1, test_data_originals(), &candidate),
+            Ok(false)
+        );
+
+        candidate[17] ^= 1;
+        assert_eq!(
+            rs_verify_recovery(4, SHARD, 2, test_data_originals(), &candidate),
+            Ok(false)
+        );
+
+        let err = rs_verify_recovery(4, SHARD, 4, test_data_originals(), &candidate).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+    }
+
+    #[test]
+    fn should_decode_all_originals_in_order() {
+        let recovery_count = 4;
+        let encoded = test_data(recovery_count);
+        let originals = test_data_originals();
+
+        let to_decode = RsShardsCollection {
+            length: 3,
+            shard_len: encoded.shard_len,
+            data: {
+                let mut data = vec![];
+                data.extend(encoded.chunk_at(2));
+                data.extend(originals.chunk_at(1));
+                data.extend(encoded.chunk_at(0));
+                data
+            },
+            indices: vec![encoded.chunk_index_at(2), 1, encoded.chunk_index_at(0)].into(),
+        };
+
+        let decoded = rs_decode_full(3, recovery_count, SHARD, to_decode).unwrap();
+
+        assert_eq!(decoded.length, 3);
+        assert_eq!(decoded.indices, Some(vec![0, 1, 2]));
+        assert_eq!(decoded.data, originals.data);
+    }
+
+    #[test]
+    fn should_encode_and_decode_shards_larger_than_u16() {
+        const LARGE_SHARD: usize = 100 * 1024;
+        let originals = RsShardsCollection {
+            length: 3,
+            shard_len: LARGE_SHARD as u32,
+            data: (0..3 * LARGE_SHARD).map(|i| (i % 251) as u8).collect(),
+            indices: None,
+        };
+        let encoded = rs_encode(2, LARGE_SHARD, &originals).unwrap();
+        assert_eq!(encoded.shard_len, LARGE_SHARD as u32);
+
+        // drop originals 0 and 2
+        let to_decode = RsShardsCollection {
+            length: 3,
+            shard_len: LARGE_SHARD as u32,
+            data: [
+                encoded.chunk_at(0),
+                originals.chunk_at(1),
+                encoded.chunk_at(1),
+            ]
+            .concat(),
+            indices: Some(vec![3, 1, 4]),
+        };
+        let decoded = rs_decode_full(3, 2, LARGE_SHARD, to_decode).unwrap();
+
+        assert_eq!(decoded.shard_len, LARGE_SHARD as u32);
+        assert_eq!(decoded.data, originals.data);
+    }
+
+    #[test]
+    fn should_decode_full_into_output() {
+        let encoded = test_data(2);
+        let shards = RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data: [encoded.chunk_at(1), &[2u8; SHARD][..], encoded.chunk_at(0)].concat(),
+            indices: Some(vec![4, 1, 3]),
+        };
+        let expected = rs_decode_full(3, 2, SHARD, &shards).unwrap();
+
+        // a re-used page buffer, larger than needed
+        let mut output = vec![0xaa; 4 * SHARD];
+        let written = rs_decode_full_into(3, 2, SHARD, &shards, output.len(), |offset, chunk| {
+            output[offset..offset + chunk.len()].copy_from_slice(chunk)
+        })
+        .unwrap();
+
+        assert_eq!(written, 3 * SHARD);
+        assert_eq!(&output[..written], expected.data);
+        assert_eq!(&output[written..], &[0xaa; SHARD]);
+
+        let err = rs_decode_full_into(3, 2, SHARD, &shards, 3 * SHARD - 1, |_, _| {}).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::OutOfBounds);
+    }
+
+    #[test]
+    fn should_list_restored_originals() {
+        let encoded = test_data(4);
+        let mut originals = test_data_originals();
+        // drop originals 0 and 2
+        originals.data.drain(2 * SHARD..);
+        originals.data.drain(..SHARD);
+        originals.length = 1;
+        originals.indices = Some(vec![1]);
+
+        let shards = SplitShards {
+            original_count: 3,
+            originals: &originals,
+            recoveries: RsShardsCollection {
+                length: 2,
+                shard_len: SHARD as u32,
+                data: encoded.data[SHARD..3 * SHARD].to_vec(),
+                indices: Some(vec![1, 2]),
+            },
+        };
+        let (decoded, restored) = decode_full_restored(3, 4, SHARD, shards).unwrap();
+
+        assert_eq!(restored, vec![0, 2]);
+        assert_eq!(decoded.data, test_data_originals().data);
+
+        // nothing to restore
+        let (_, restored) = decode_full_restored(3, 4, SHARD, test_data_originals()).unwrap();
+        assert_eq!(restored, Vec::<u16>::new());
+    }
+
+    #[test]
+    fn should_compute_shard_range_without_overflow() {
+        assert_eq!(shard_range(2, 64, 192), Ok((128, 192)));
+        assert_eq!(
+            shard_range(2, 64, 191).unwrap_err().code(),
+            RsErrorCode::OutOfBounds
+        );
+
+        // 70000 * 65535 overflows `u32` and would wrap to a small offset
+        let err = shard_range(70000, 65535, u32::MAX).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::OutOfBounds);
+        assert!(shard_range(65535, 65535, u32::MAX).is_ok());
+    }
+
+    #[test]
+    fn should_init_more_than_once() {
+        init();
+        init();
+    }
+
+    #[test]
+    fn should_report_versions() {
+        let version = version();
+
+        assert!(version.contains(env!("CARGO_PKG_VERSION")));
+        let dotted = |word: &str| {
+            let parts: Vec<_> = word
+                .trim_matches(|c| c == '(' || c == ')')
+                .split('.')
+                .collect();
+            parts.len() == 3 && parts.iter().all(|p| p.parse::<u32>().is_ok())
+        };
+        assert_eq!(version.split(' ').filter(|w| dotted(w)).count(), 2);
+    }
+
+    #[test]
+    fn should_pin_reported_reed_solomon_version() {
+        let manifest = include_str!("../Cargo.toml");
+        let dependency = manifest
+            .lines()
+            .find(|line| line.starts_with("reed-solomon = "))
+            .unwrap();
+
+        assert!(dependency.contains(&format!("version = \"={}\"", REED_SOLOMON_VERSION)));
+    }
+
+    #[test]
+    fn should_derive_shard_len_of_chunks() {
+        assert_eq!(common_shard_len([64, 64, 64].into_iter()), Ok(64));
+
+        let err = common_shard_len([64, 32, 64].into_iter()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(
+            err.message(),
+            "All chunks must have the same length (got 64 and 32)."
+        );
+
+        let err = common_shard_len([].into_iter()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[test]
+    fn should_decode_full_in_order_after_dropping_scattered_originals() {
+        let originals = RsShardsCollection {
+            length: 6,
+            shard_len: SHARD as u32,
+            data: (0..6 * SHARD)
+                .map(|i| (i / SHARD * 10 + i % 7) as u8)
+                .collect(),
+            indices: None,
+        };
+        let encoded = rs_encode(3, SHARD, &originals).unwrap();
+
+        // keep originals 1, 3 and 4 (out of order) and all recovery shards
+        let kept = [4, 1, 3];
+        let mut data = vec![];
+        for idx in kept {
+            data.extend(originals.chunk_at(idx));
+        }
+        data.extend(&encoded.data);
+        let to_decode = RsShardsCollection {
+            length: 6,
+            shard_len: SHARD as u32,
+            data,
+            indices: Some(vec![4, 1, 3, 6, 7, 8]),
+        };
+
+        let decoded = rs_decode_full(6, 3, SHARD, to_decode).unwrap();
+
+        assert_eq!(decoded.length, 6);
+        assert_eq!(decoded.indices, Some((0..6).collect()));
+        for i in 0..6 {
+            assert_eq!(decoded.chunk_at(i), originals.chunk_at(i));
+        }
+        assert_eq!(decoded.data, originals.data);
+    }
 
-    let decoding_result = decoder.decode()?;
+    #[test]
+    fn should_decode_with_erasure_bitmap() {
+        let encoded = test_data(2);
+        let originals = test_data_originals();
+        // originals 0 and 2 and recovery shard 4 are missing
+        let bitmap = [0b0001_0101];
+        let indices = present_indices(&bitmap, 5).unwrap();
+        assert_eq!(indices, vec![1, 3]);
 
-    let mut indices = vec![];
-    let mut data = vec![];
-    for (idx, shard) in decoding_result.restored_original_iter() {
-        indices.push(idx as u16);
-        data.extend(shard);
+        let mut to_decode = RsShardsCollection {
+            length: 2,
+            shard_len: SHARD as u32,
+            data: [originals.chunk_at(1), encoded.chunk_at(0)].concat(),
+            indices: Some(indices),
+        };
+        let err = rs_decode(3, 2, SHARD, &to_decode).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+
+        // recovery shard 4 is present after all
+        let bitmap = [0b0000_0101];
+        to_decode.indices = Some(present_indices(&bitmap, 5).unwrap());
+        to_decode.data.extend(encoded.chunk_at(1));
+        to_decode.length = 3;
+        let by_bitmap = rs_decode(3, 2, SHARD, &to_decode).unwrap();
+
+        to_decode.indices = Some(vec![1, 3, 4]);
+        let by_indices = rs_decode(3, 2, SHARD, &to_decode).unwrap();
+        assert_eq!(by_bitmap.indices, by_indices.indices);
+        assert_eq!(by_bitmap.data, by_indices.data);
+
+        let err = present_indices(&[0], 9).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(present_indices(&[0xff, 0xff], 9), Ok(vec![]));
     }
 
-    Ok(RsShardsCollection {
-        length: indices.len(),
-        shard_len: shards.shard_len,
-        indices: Some(indices),
-        data,
-    })
-}
+    #[test]
+    fn should_checksum_shards_incrementally() {
+        let shards = test_data_originals();
+        let mut content = vec![];
+        content.extend((SHARD as u32).to_le_bytes());
+        content.extend(3u32.to_le_bytes());
+        content.extend([0, 0, 1, 0, 2, 0]);
+        content.extend(&shards.data);
+        assert_eq!(shards_checksum(&shards), integrity::crc32(&content));
 
-#[wasm_bindgen]
-pub fn encode(recovery_count: u16, shards: ShardsCollection) -> Result<ShardsCollection, String> {
-    let result = rs_encode(recovery_count as usize, shards.into()).map_err(|e| e.to_string())?;
+        // implicit and explicit sequential indices are the same content
+        let mut explicit = test_data_originals();
+        explicit.indices = Some(vec![0, 1, 2]);
+        assert_eq!(shards_checksum(&explicit), shards_checksum(&shards));
 
-    Ok(result.into())
-}
+        explicit.indices = Some(vec![0, 2, 1]);
+        assert_ne!(shards_checksum(&explicit), shards_checksum(&shards));
+    }
 
-#[wasm_bindgen]
-pub fn decode(
-    original_count: u16,
-    recovery_count: u16,
-    shards: ShardsCollection,
-) -> Result<ShardsCollection, String> {
-    let result = rs_decode(
-        original_count as usize,
-        recovery_count as usize,
-        shards.into(),
-    )
-    .map_err(|e| e.to_string())?;
+    #[test]
+    fn should_compare_equal_collections() {
+        let implicit = test_data_originals();
+        let mut explicit = test_data_originals();
+        explicit.indices = Some(vec![0, 1, 2]);
 
-    Ok(result.into())
+        assert!(shards_equal(&implicit, &implicit));
+        assert!(shards_equal(&implicit, &explicit));
+        assert!(shards_equal(&explicit, &implicit));
+    }
+
+    #[test]
+    fn should_compare_collections_with_different_data() {
+        let a = test_data_originals();
+        let mut b = test_data_originals();
+        b.data[2 * SHARD + 1] ^= 1;
+
+        assert!(!shards_equal(&a, &b));
+
+        b.data.truncate(2 * SHARD);
+        b.length = 2;
+        assert!(!shards_equal(&a, &b));
+    }
+
+    #[test]
+    fn should_compare_collections_with_different_indices() {
+        let a = test_data_originals();
+        let mut b = test_data_originals();
+        b.indices = Some(vec![0, 2, 1]);
+
+        assert!(!shards_equal(&a, &b));
+
+        b.indices = Some(vec![3, 4, 5]);
+        assert!(!shards_equal(&a, &b));
+    }
 }
 
-#[cfg(test)]
-mod tests {
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
     use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
     const SHARD: usize = 64;
 
-    fn test_data(recovery_count: usize) -> RsShardsCollection {
+    #[wasm_bindgen_test]
+    fn should_create_collection_from_chunks() {
+        let chunks = js_sys::Array::new();
+        for i in 1..=3u8 {
+            chunks.push(&js_sys::Uint8Array::from(&[i; SHARD][..]));
+        }
+
+        let collection = ShardsCollection::from_chunks(chunks, None).unwrap();
+
+        assert_eq!(collection.length, 3);
+        assert_eq!(collection.shard_len, SHARD as u32);
+        let collection = RsShardsCollection::from(collection);
+        for i in 0..3 {
+            assert_eq!(collection.chunk_at(i), &[i as u8 + 1; SHARD]);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn should_split_collection_into_chunks() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| i as u8).collect();
+        let collection =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+
+        let chunks = collection.to_chunks();
+
+        assert_eq!(chunks.length(), collection.length);
+        for (idx, chunk) in chunks.iter().enumerate() {
+            let chunk: js_sys::Uint8Array = chunk.dyn_into().unwrap();
+            assert_eq!(chunk.to_vec(), &data[idx * SHARD..(idx + 1) * SHARD]);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn should_list_entries_of_decoded_shards() {
+        let mut originals = vec![];
+        for i in 1..=3u8 {
+            originals.extend([i; SHARD]);
+        }
+        let originals =
+            ShardsCollection::new(SHARD as u32, originals.as_slice().into(), None, None).unwrap();
+        let encoded = RsShardsCollection::from(encode(2, SHARD as u32, originals).unwrap());
+
         let mut data = vec![];
-        data.extend([1u8; SHARD]);
         data.extend([2u8; SHARD]);
-        data.extend([3u8; SHARD]);
+        data.extend(encoded.chunk_at(0));
+        data.extend(encoded.chunk_at(1));
+        let indices = [1u16, 3, 4];
+        let to_decode = ShardsCollection::new(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
 
-        let shards = RsShardsCollection {
-            length: 3,
-            shard_len: SHARD as u16,
-            data,
-            indices: None,
+        let entries = decode(3, 2, SHARD as u32, to_decode).unwrap().entries();
+
+        assert_eq!(entries.length(), 2);
+        for (entry, (expected_idx, expected_byte)) in entries.iter().zip([(0, 1u8), (2, 3u8)]) {
+            let entry: js_sys::Array = entry.dyn_into().unwrap();
+            assert_eq!(entry.get(0).as_f64(), Some(expected_idx as f64));
+            let shard: js_sys::Uint8Array = entry.get(1).dyn_into().unwrap();
+            assert_eq!(shard.to_vec(), vec![expected_byte; SHARD]);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn should_access_chunks_with_methods() {
+        let data: Vec<u8> = (0..2 * SHARD).map(|i| i as u8).collect();
+        let indices = [5u16, 1];
+        let collection = ShardsCollection::new(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(collection.len(), 2);
+        assert_eq!(collection.chunk_at(1).unwrap().to_vec(), &data[SHARD..]);
+        assert_eq!(collection.chunk_index_at(0).unwrap(), 5);
+        assert_eq!(collection.chunk_index_at(1).unwrap(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_slice_collection() {
+        let data: Vec<u8> = (0..10 * SHARD).map(|i| (i / SHARD) as u8).collect();
+        let collection =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+
+        let sliced = collection.slice(3, 7).unwrap();
+
+        assert_eq!(sliced.len(), 4);
+        for i in 0..4 {
+            assert_eq!(sliced.chunk_index_at(i).unwrap(), i as u16 + 3);
+            assert_eq!(
+                sliced.chunk_at(i).unwrap().to_vec(),
+                vec![i as u8 + 3; SHARD]
+            );
+        }
+
+        let resliced = sliced.slice(1, 2).unwrap();
+        assert_eq!(resliced.chunk_index_at(0).unwrap(), 4);
+
+        assert!(collection.slice(7, 11).is_err());
+        assert!(collection.slice(7, 3).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn should_concat_collections_and_decode() {
+        let originals: Vec<u8> = (0..4 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let encoded = encode(
+            4,
+            SHARD as u32,
+            ShardsCollection::new(SHARD as u32, originals.as_slice().into(), None, None).unwrap(),
+        )
+        .unwrap();
+
+        let first = {
+            let mut data = vec![];
+            data.extend(&originals[SHARD..2 * SHARD]);
+            data.extend(&originals[3 * SHARD..]);
+            data.extend(encoded.chunk_at(0).unwrap().to_vec());
+            let indices = [1u16, 3, 4];
+            ShardsCollection::new(
+                SHARD as u32,
+                data.as_slice().into(),
+                Some(indices.as_slice().into()),
+                None,
+            )
+            .unwrap()
         };
+        let second = encoded.slice(1, 4).unwrap();
 
-        rs_encode(recovery_count, shards).unwrap()
+        let merged = first.concat(&second).unwrap();
+        assert_eq!(merged.len(), 6);
+        assert_eq!(merged.chunk_index_at(3).unwrap(), 5);
+
+        let decoded = RsShardsCollection::from(decode_full(4, 4, SHARD as u32, merged).unwrap());
+        assert_eq!(decoded.data, originals);
+
+        let other = ShardsCollection::new(2, vec![0u8; 4].as_slice().into(), None, None).unwrap();
+        let err = first.concat(&other).err().unwrap();
+        assert_eq!(err.code(), RsErrorCode::DifferentShardSize);
     }
 
-    #[test]
-    fn should_encode_shards() {
-        let recovery_count = 5;
-        let encoded = test_data(recovery_count);
+    #[wasm_bindgen_test]
+    fn should_clone_collection() {
+        let data = vec![7u8; 2 * SHARD];
+        let indices = [3u16, 1];
+        let collection = ShardsCollection::new(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
 
-        assert_eq!(encoded.length, recovery_count);
-        assert_eq!(encoded.shard_len, SHARD as u16);
-        assert_eq!(encoded.chunk_index_at(0), 3);
-        assert_eq!(encoded.chunk_index_at(1), 4);
-        assert_eq!(encoded.chunk_index_at(2), 5);
-        assert_eq!(encoded.chunk_index_at(3), 6);
-        assert_eq!(encoded.chunk_index_at(4), 7);
-        assert_eq!(
-            encoded.chunk_at(0),
-            &[
-                156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156,
-                156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 156, 15,
-                15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
-                15, 15, 15, 15, 15, 15, 15, 15, 15, 15
-            ]
-        );
-        assert_eq!(
-            encoded.chunk_at(1),
-            &[
-                159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159,
-                159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 159, 12,
-                12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12,
-                12, 12, 12, 12, 12, 12, 12, 12, 12, 12
-            ]
-        );
-        assert_eq!(
-            encoded.chunk_at(2),
-            &[
-                158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158,
-                158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 158, 13,
-                13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13, 13,
-                13, 13, 13, 13, 13, 13, 13, 13, 13, 13
-            ]
+        let cloned = collection.deep_clone();
+        collection.data.fill(0, 0, collection.data.length());
+        collection.indices.as_ref().unwrap().fill(0, 0, 2);
+        drop(collection);
+
+        assert_eq!(cloned.data.to_vec(), data);
+        assert_eq!(cloned.chunk_index_at(0).unwrap(), 3);
+        assert_eq!(cloned.chunk_index_at(1).unwrap(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_read_shards_straight_from_js_memory() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| i as u8).collect();
+        let shards =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        let copied = RsShardsCollection::from(shards.deep_clone());
+
+        let from_js = rs_encode(4, SHARD, shards).unwrap();
+        let from_wasm = rs_encode(4, SHARD, copied).unwrap();
+
+        assert_eq!(from_js.indices, from_wasm.indices);
+        assert_eq!(from_js.data, from_wasm.data);
+
+        let recovery: ShardsCollection = from_js.into();
+        let decoded = rs_decode_full(3, 4, SHARD, recovery.slice(1, 4).unwrap()).unwrap();
+        assert_eq!(decoded.data, data);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_keep_payload_len_of_padded_shards() {
+        // 3 full shards and a final one with just 40 meaningful bytes.
+        let payload: Vec<u8> = (0..3 * SHARD + 40).map(|i| (i % 251) as u8 + 1).collect();
+        let protected = protect(payload.as_slice().into(), SHARD as u32, 2, None, None).unwrap();
+
+        assert_eq!(protected.payload_len(), Some(payload.len() as u32));
+        let last = protected.chunk_at(3).unwrap().to_vec();
+        assert_eq!(last.len(), SHARD);
+        assert_eq!(&last[..40], &payload[3 * SHARD..]);
+        assert_eq!(&last[40..], &[0u8; SHARD - 40]);
+
+        let originals = protected.slice(0, 4).unwrap();
+        let collection = ShardsCollection::new(
+            SHARD as u32,
+            originals.data.clone(),
+            None,
+            protected.payload_len(),
+        )
+        .unwrap();
+        assert_eq!(collection.payload_len(), Some(payload.len() as u32));
+
+        let recovered = recover(
+            4,
+            2,
+            SHARD as u32,
+            collection.payload_len().unwrap(),
+            protected.slice(2, 6).unwrap(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(recovered.to_vec(), payload);
+
+        let err = ShardsCollection::new(
+            SHARD as u32,
+            originals.data.clone(),
+            None,
+            Some(4 * SHARD as u32 + 1),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidPayloadLength);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_decode_collection_from_array_buffer() {
+        let originals: Vec<u8> = (0..3 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let encoded = RsShardsCollection::from(
+            encode(
+                2,
+                SHARD as u32,
+                ShardsCollection::new(SHARD as u32, originals.as_slice().into(), None, None)
+                    .unwrap(),
+            )
+            .unwrap(),
         );
-        assert_eq!(
-            encoded.chunk_at(3),
-            &[
-                157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157,
-                157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 157, 14,
-                14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14, 14,
-                14, 14, 14, 14, 14, 14, 14, 14, 14, 14
-            ]
+
+        let buffer = js_sys::ArrayBuffer::new(3 * SHARD as u32);
+        let view = js_sys::Uint8Array::new(&buffer);
+        view.subarray(0, SHARD as u32)
+            .copy_from(&originals[..SHARD]);
+        view.subarray(SHARD as u32, 3 * SHARD as u32)
+            .copy_from(&encoded.data);
+        let indices = [0u16, 3, 4];
+
+        let collection = ShardsCollection::from_array_buffer(
+            SHARD as u32,
+            buffer,
+            Some(indices.as_slice().into()),
+        )
+        .unwrap();
+        let decoded = decode_full(3, 2, SHARD as u32, collection).unwrap();
+        assert_eq!(decoded.restored_indices().unwrap().to_vec(), vec![1, 2]);
+        assert_eq!(decoded.slice(0, 3).unwrap().restored_indices(), None);
+
+        let decoded = RsShardsCollection::from(decoded);
+        assert_eq!(decoded.data, originals);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_reset_collection_in_place() {
+        let mut collection = ShardsCollection::new(
+            SHARD as u32,
+            vec![1u8; 3 * SHARD].as_slice().into(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        let data: Vec<u8> = (0..5 * 32).map(|i| (i / 32) as u8).collect();
+        let indices = [9u16, 8, 7, 6, 5];
+        collection
+            .reset(32, data.as_slice().into(), Some(indices.as_slice().into()))
+            .unwrap();
+
+        assert_eq!(collection.len(), 5);
+        assert_eq!(collection.shard_len, 32);
+        assert_eq!(collection.chunk_at(4).unwrap().to_vec(), vec![4u8; 32]);
+        assert_eq!(collection.chunk_index_at(4).unwrap(), 5);
+
+        assert!(collection.reset(64, data.as_slice().into(), None).is_err());
+        assert_eq!(collection.len(), 5);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_assemble_collection_shard_by_shard() {
+        let originals: Vec<u8> = (0..3 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let encoded = RsShardsCollection::from(
+            encode(
+                2,
+                SHARD as u32,
+                ShardsCollection::new(SHARD as u32, originals.as_slice().into(), None, None)
+                    .unwrap(),
+            )
+            .unwrap(),
         );
+
+        let indices = [4u16, 0, 3];
+        let mut collection = ShardsCollection::new(
+            SHARD as u32,
+            js_sys::Uint8Array::new_with_length(3 * SHARD as u32),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
+        collection.set_shard(2, encoded.chunk_at(0).into()).unwrap();
+        collection.set_shard(0, encoded.chunk_at(1).into()).unwrap();
+        collection.set_shard(1, originals[..SHARD].into()).unwrap();
+
+        assert!(collection.set_shard(3, originals[..SHARD].into()).is_err());
+        assert!(collection
+            .set_shard(1, originals[..SHARD - 2].into())
+            .is_err());
+
+        let decoded =
+            RsShardsCollection::from(decode_full(3, 2, SHARD as u32, collection).unwrap());
+        assert_eq!(decoded.data, originals);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_create_empty_collection() {
+        let empty = ShardsCollection::new(
+            SHARD as u32,
+            js_sys::Uint8Array::new_with_length(0),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(empty.is_empty());
+        assert_eq!(empty.len(), 0);
+        assert!(empty.chunk_at(0).is_err());
+
+        let err = encode(2, SHARD as u32, empty).err().unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_report_total_bytes() {
+        let indices = [1u16, 4, 0];
+        let collection = ShardsCollection::new(
+            SHARD as u32,
+            vec![0u8; 3 * SHARD].as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(collection.shard_len, SHARD as u32);
         assert_eq!(
-            encoded.chunk_at(4),
-            &[
-                175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175,
-                175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 175, 8,
-                8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8, 8,
-                8, 8, 8
-            ]
+            collection.total_bytes(),
+            collection.len() * collection.shard_len as usize
         );
     }
 
-    #[test]
-    fn should_decode_shards() {
-        let recovery_count = 6;
-        let encoded = test_data(recovery_count);
+    #[wasm_bindgen_test]
+    fn should_release_data() {
+        let indices = [1u16, 4, 0];
+        let mut collection = ShardsCollection::new(
+            SHARD as u32,
+            vec![0u8; 3 * SHARD].as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
 
-        let to_decode = RsShardsCollection {
-            length: 3,
-            shard_len: encoded.shard_len,
-            data: {
-                let mut data = vec![];
-                data.extend(encoded.chunk_at(0));
-                data.extend(&[2u8; SHARD]);
-                data.extend(encoded.chunk_at(4));
-                data
+        collection.release_data();
+
+        assert_eq!(collection.total_bytes(), 0);
+        assert!(collection.is_empty());
+        assert_eq!(collection.shard_len, SHARD as u32);
+        assert_eq!(collection.validate(), Ok(()));
+    }
+
+    #[wasm_bindgen_test]
+    fn should_encode_collection_from_clamped_array() {
+        let data: Vec<u8> = (0..4 * SHARD).map(|i| (i * 3) as u8).collect();
+        let clamped = js_sys::Uint8ClampedArray::new_with_length(4 * SHARD as u32);
+        clamped.copy_from(&data);
+
+        let collection =
+            ShardsCollection::from_typed_array(SHARD as u32, clamped.into(), None).unwrap();
+        assert_eq!(collection.len(), 4);
+
+        let encoded = rs_encode(2, SHARD, collection).unwrap();
+        let expected = rs_encode(
+            2,
+            SHARD,
+            RsShardsCollection {
+                length: 4,
+                shard_len: SHARD as u32,
+                data,
+                indices: None,
             },
-            indices: vec![encoded.chunk_index_at(0), 1, encoded.chunk_index_at(4)].into(),
+        )
+        .unwrap();
+        assert_eq!(encoded.data, expected.data);
+
+        let wide = js_sys::Uint16Array::new_with_length(2 * SHARD as u32);
+        let err = ShardsCollection::from_typed_array(SHARD as u32, wide.into(), None)
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_expose_shard_counts_of_encoded_collection() {
+        let originals = ShardsCollection::new(
+            SHARD as u32,
+            vec![5u8; 3 * SHARD].as_slice().into(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(originals.original_count(), None);
+        assert_eq!(originals.recovery_count(), None);
+
+        let encoded = encode_systematic(4, SHARD as u32, originals).unwrap();
+
+        assert_eq!(encoded.original_count(), Some(3));
+        assert_eq!(encoded.recovery_count(), Some(4));
+    }
+
+    #[wasm_bindgen_test]
+    fn should_report_shards_needed_to_decode() {
+        let data = vec![1u8; 5 * SHARD];
+        let originals =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        assert_eq!(originals.shards_needed(), None);
+
+        let encoded = encode_systematic(2, SHARD as u32, originals).unwrap();
+        assert_eq!(encoded.shards_needed(), Some(5));
+
+        // any subset of that size decodes
+        let subset = encoded.slice(2, 7).unwrap();
+        assert_eq!(subset.shards_needed(), Some(5));
+        assert!(decode_auto(subset).is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn should_decode_with_parameters_from_config() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let originals =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        let encoded = encode_systematic(2, SHARD as u32, originals).unwrap();
+
+        let decoded = RsShardsCollection::from(decode_auto(encoded.slice(2, 5).unwrap()).unwrap());
+
+        assert_eq!(decoded.indices, Some(vec![0, 1]));
+        assert_eq!(decoded.data, &data[..2 * SHARD]);
+
+        let without_config =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        let err = decode_auto(without_config).err().unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_not_abort_on_short_indices() {
+        let indices = [7u16];
+        // bypass the constructor validation
+        let collection = ShardsCollection {
+            length: 2,
+            shard_len: SHARD as u32,
+            data: vec![0u8; 2 * SHARD].as_slice().into(),
+            indices: Some(indices.as_slice().into()),
+            payload_len: None,
+            config: None,
+            restored: None,
         };
 
-        let decoded = rs_decode(3, recovery_count, to_decode).unwrap();
+        assert_eq!(collection.chunk_index_at(0), Ok(7));
+        let err = collection.chunk_index_at(1).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::OutOfBounds);
+        assert!(collection.chunk_index_at(2).is_err());
+        assert!(collection.validate().is_err());
+    }
 
-        assert_eq!(decoded.length, 2);
-        assert_eq!(decoded.shard_len, encoded.shard_len);
-        assert_eq!(decoded.chunk_index_at(0), 0u16);
-        assert_eq!(decoded.chunk_index_at(1), 2u16);
-        assert_eq!(decoded.chunk_at(0), &[1u8; SHARD]);
-        assert_eq!(decoded.chunk_at(1), &[3u8; SHARD]);
+    #[wasm_bindgen_test]
+    fn should_copy_shard_into_caller_buffer() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let mut collection =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        let out = js_sys::Uint8Array::new_with_length(SHARD as u32);
+        let view = collection.chunk_at(1).unwrap();
+
+        collection.copy_shard_into(1, out.clone()).unwrap();
+        collection
+            .set_shard(1, vec![0u8; SHARD].as_slice().into())
+            .unwrap();
+
+        assert_eq!(out.to_vec(), vec![2u8; SHARD]);
+        assert_eq!(view.to_vec(), vec![0u8; SHARD]);
+
+        let err = collection.copy_shard_into(3, out.clone()).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::OutOfBounds);
+        let short = js_sys::Uint8Array::new_with_length(SHARD as u32 - 2);
+        let err = collection.copy_shard_into(0, short).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::DifferentShardSize);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_attach_indices_to_collection() {
+        let collection = ShardsCollection::new(
+            SHARD as u32,
+            vec![1u8; 3 * SHARD].as_slice().into(),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(collection.chunk_index_at(2), Ok(2));
+
+        let indices = [5u16, 0, 3];
+        let collection = collection.with_indices(indices.as_slice().into()).unwrap();
+
+        assert_eq!(collection.chunk_index_at(0), Ok(5));
+        assert_eq!(collection.chunk_index_at(1), Ok(0));
+        assert_eq!(collection.chunk_index_at(2), Ok(3));
+
+        let err = collection
+            .with_indices([1u16, 2].as_slice().into())
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_extract_recovery_shards() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let originals =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        let encoded = encode_systematic(2, SHARD as u32, originals).unwrap();
+        let expected = RsShardsCollection::from(encoded.slice(3, 5).unwrap());
+
+        let recovery = encoded.recovery_only(3);
+        assert_eq!(recovery.config(), encoded.config());
+
+        let recovery = RsShardsCollection::from(recovery);
+        assert_eq!(recovery.indices, Some(vec![3, 4]));
+        assert_eq!(recovery.data, expected.data);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_extract_original_shards() {
+        let data: Vec<u8> = (0..4 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect();
+        let indices = [5u16, 2, 4, 0];
+        let received = ShardsCollection::new(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
+
+        let originals = RsShardsCollection::from(received.originals_only(3));
+
+        assert_eq!(originals.indices, Some(vec![2, 0]));
+        assert_eq!(originals.chunk_at(0), &[2u8; SHARD]);
+        assert_eq!(originals.chunk_at(1), &[4u8; SHARD]);
+        assert_eq!(received.recovery_only(3).length, 2);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_checksum_collection_content() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| (i % 251) as u8).collect();
+        let collection =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+        let indices = [0u16, 1, 2];
+        let same = ShardsCollection::new(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(indices.as_slice().into()),
+            None,
+        )
+        .unwrap();
+        assert_eq!(collection.checksum(), same.checksum());
+        assert_eq!(collection.checksum(), collection.deep_clone().checksum());
+
+        let mut flipped = data.clone();
+        flipped[100] ^= 1;
+        let flipped =
+            ShardsCollection::new(SHARD as u32, flipped.as_slice().into(), None, None).unwrap();
+        assert_ne!(collection.checksum(), flipped.checksum());
+
+        let reordered = [0u16, 2, 1];
+        let reordered = ShardsCollection::new(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(reordered.as_slice().into()),
+            None,
+        )
+        .unwrap();
+        assert_ne!(collection.checksum(), reordered.checksum());
+    }
+
+    #[wasm_bindgen_test]
+    fn should_assemble_zeros_collection() {
+        let mut collection = ShardsCollection::zeros(SHARD as u32, 4).unwrap();
+        assert_eq!(collection.len(), 4);
+        assert_eq!(collection.total_bytes(), 4 * SHARD);
+
+        collection
+            .set_shard(1, vec![7u8; SHARD].as_slice().into())
+            .unwrap();
+        collection
+            .set_shard(3, vec![9u8; SHARD].as_slice().into())
+            .unwrap();
+
+        assert_eq!(collection.chunk_at(0).unwrap().to_vec(), vec![0u8; SHARD]);
+        assert_eq!(collection.chunk_at(1).unwrap().to_vec(), vec![7u8; SHARD]);
+        assert_eq!(collection.chunk_at(2).unwrap().to_vec(), vec![0u8; SHARD]);
+        assert_eq!(collection.chunk_at(3).unwrap().to_vec(), vec![9u8; SHARD]);
+
+        let err = ShardsCollection::zeros(0, 4).err().unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_decode_large_frames_added_from_js() {
+        const LARGE: usize = 32 * 1024;
+        let originals = RsShardsCollection {
+            length: 8,
+            shard_len: LARGE as u32,
+            data: (0..8 * LARGE).map(|i| (i % 253) as u8).collect(),
+            indices: None,
+        };
+        let recovery = rs_encode(4, LARGE, &originals).unwrap();
+        let mut decoder = Decoder::new(8, 4, LARGE as u32).unwrap();
+
+        // run a few frames, so that the same buffer is re-used
+        for _ in 0..3 {
+            for i in 0..4 {
+                let index = recovery.chunk_index_at(i) - 8;
+                decoder
+                    .add_recovery_shard(index, recovery.chunk_at(i).into())
+                    .unwrap();
+            }
+            for i in 4..8 {
+                decoder
+                    .add_original_shard(i as u16, originals.chunk_at(i).into())
+                    .unwrap();
+            }
+
+            let decoded = RsShardsCollection::from(decoder.decode().unwrap());
+            assert_eq!(decoded.indices, Some(vec![0, 1, 2, 3]));
+            assert_eq!(decoded.data, &originals.data[..4 * LARGE]);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn should_reject_data_not_multiple_of_shard_len() {
+        // forgot to pad the last shard
+        let data = vec![1u8; 3 * SHARD - 5];
+        let err = ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None)
+            .err()
+            .unwrap();
+
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+        assert_eq!(
+            err.message(),
+            "data length (187) is not a multiple of shard_len (64)"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn should_reject_out_of_range_index_on_construction() {
+        let data = vec![0u8; 3 * SHARD];
+        let indices = [0u16, 7, 1];
+
+        let collection =
+            ShardsCollection::new_for_decode(SHARD as u32, data.as_slice().into(), None, 3, 2);
+        assert!(collection.is_ok());
+
+        let err = ShardsCollection::new_for_decode(
+            SHARD as u32,
+            data.as_slice().into(),
+            Some(indices.as_slice().into()),
+            3,
+            2,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+        assert_eq!(
+            err.message(),
+            "Shard index 7 (at position 1) is out of range (original_count + recovery_count = 5)."
+        );
     }
 }
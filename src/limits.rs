@@ -0,0 +1,179 @@
+//! Limits of the parameters accepted by `reed_solomon`.
+//!
+//! Exposed so that JS callers can validate their configuration up front.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// The total number of shards is bounded by the order of the 16-bit Galois field.
+pub(crate) const MAX_SHARDS: usize = 65_536;
+/// Shards are processed as 16-bit words, so their size must be a multiple of 2.
+pub(crate) const SHARD_BYTES_MULTIPLE: usize = 2;
+/// The smallest non-empty shard.
+pub(crate) const MIN_SHARD_BYTES: usize = SHARD_BYTES_MULTIPLE;
+/// The largest valid shard representable as `u32`.
+pub(crate) const MAX_SHARD_BYTES: usize =
+    (u32::MAX as usize) / SHARD_BYTES_MULTIPLE * SHARD_BYTES_MULTIPLE;
+
+/// Maximal total number of shards (`original_count + recovery_count`).
+///
+/// NOTE that not every split of that total is supported, the smaller of
+/// the two counts, rounded up to a power of two, plus the other one
+/// must not exceed the limit.
+#[wasm_bindgen]
+pub fn max_shards() -> u32 {
+    MAX_SHARDS as u32
+}
+
+/// Minimal size of a shard in bytes.
+#[wasm_bindgen]
+pub fn min_shard_bytes() -> u32 {
+    MIN_SHARD_BYTES as u32
+}
+
+/// Shard size in bytes must be a multiple of that value.
+#[wasm_bindgen]
+pub fn shard_bytes_multiple() -> u32 {
+    SHARD_BYTES_MULTIPLE as u32
+}
+
+/// Maximal `recovery_count` supported together with given `original_count`.
+///
+/// Returns `0` if `original_count` itself is not supported.
+#[wasm_bindgen]
+pub fn max_recovery_count(original_count: u16) -> u16 {
+    max_counterpart(original_count as usize) as u16
+}
+
+/// Maximal `original_count` supported together with given `recovery_count`.
+///
+/// Returns `0` if `recovery_count` itself is not supported.
+#[wasm_bindgen]
+pub fn max_original_count(recovery_count: u16) -> u16 {
+    max_counterpart(recovery_count as usize) as u16
+}
+
+/// Upper bound of the memory taken by the lookup tables of `reed_solomon` engines.
+///
+/// The tables are computed once (on first use) and kept for the lifetime of the module.
+const TABLES_BYTES: u64 = 9 * 1024 * 1024;
+
+/// Estimate (conservatively) the WASM memory needed to encode or decode with given parameters.
+///
+/// Accounts for the original and recovery shards, the working buffers of
+/// the encoder/decoder (which are rounded up to powers of two) and the lookup tables.
+/// The result is an upper bound, meant to warn before running out of memory.
+#[wasm_bindgen]
+pub fn estimate_memory_bytes(original_count: u16, recovery_count: u16, shard_bytes: u32) -> u64 {
+    let total = original_count as u64 + recovery_count as u64;
+    let shard_bytes = shard_bytes as u64;
+    let shards = total * shard_bytes;
+    let work = 2 * total.next_power_of_two() * shard_bytes;
+
+    TABLES_BYTES + shards + work
+}
+
+/// The limits are symmetric: one of the counts, rounded up to a power of two,
+/// plus the other one must not exceed [`MAX_SHARDS`].
+fn max_counterpart(count: usize) -> usize {
+    if count == 0 || count >= MAX_SHARDS {
+        return 0;
+    }
+
+    let rounded_other = prev_power_of_two(MAX_SHARDS - count);
+    let rounded_this = MAX_SHARDS.saturating_sub(count.next_power_of_two());
+    rounded_other.max(rounded_this).min(MAX_SHARDS - 1)
+}
+
+/// The largest power of two not greater than `n` (`n > 0`).
+fn prev_power_of_two(n: usize) -> usize {
+    1 << (usize::BITS - 1 - n.leading_zeros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rs_encode, RsShardsCollection};
+    use reed_solomon::ReedSolomonEncoder;
+
+    #[test]
+    fn should_accept_minimal_shard_bytes() {
+        let shard_bytes = min_shard_bytes() as usize;
+
+        assert!(ReedSolomonEncoder::new(1, 1, shard_bytes).is_ok());
+        assert!(
+            ReedSolomonEncoder::new(1, 1, shard_bytes - shard_bytes_multiple() as usize).is_err()
+        );
+    }
+
+    #[test]
+    fn should_require_shard_bytes_multiple() {
+        let multiple = shard_bytes_multiple() as usize;
+
+        assert!(ReedSolomonEncoder::new(1, 1, 64 * multiple).is_ok());
+        assert!(ReedSolomonEncoder::new(1, 1, 64 * multiple + 1).is_err());
+    }
+
+    #[test]
+    fn should_accept_up_to_max_shards() {
+        let half = max_shards() as usize / 2;
+        let shard_bytes = min_shard_bytes() as usize;
+
+        assert!(ReedSolomonEncoder::new(half, half, shard_bytes).is_ok());
+        assert!(ReedSolomonEncoder::new(half + 1, half, shard_bytes).is_err());
+        assert!(ReedSolomonEncoder::new(half, half + 1, shard_bytes).is_err());
+    }
+
+    #[test]
+    fn should_encode_up_to_max_recovery_count() {
+        let original_count = 40_000;
+        let max = max_recovery_count(original_count as u16) as usize;
+        assert_eq!(max, 16_384);
+
+        let originals = || RsShardsCollection {
+            length: original_count,
+            shard_len: 2,
+            data: vec![7; original_count * 2],
+            indices: None,
+        };
+        assert!(rs_encode(max, 2, originals()).is_ok());
+        assert!(rs_encode(max + 1, 2, originals()).is_err());
+    }
+
+    #[test]
+    fn should_match_supported_shard_counts() {
+        for count in [1, 2, 3, 1000, 4096, 4097, 32_767, 32_768, 32_769, 65_535] {
+            let max = max_recovery_count(count as u16) as usize;
+            assert!(
+                ReedSolomonEncoder::supports(count, max),
+                "{} + {}",
+                count,
+                max
+            );
+            assert!(
+                !ReedSolomonEncoder::supports(count, max + 1),
+                "{} + {}",
+                count,
+                max + 1
+            );
+
+            assert_eq!(max_original_count(count as u16) as usize, max);
+            assert!(ReedSolomonEncoder::supports(max, count));
+        }
+
+        assert_eq!(max_recovery_count(0), 0);
+        assert_eq!(max_original_count(0), 0);
+    }
+
+    #[test]
+    fn should_estimate_growing_memory_use() {
+        let base = estimate_memory_bytes(100, 50, 1024);
+        assert!(base >= 150 * 1024);
+
+        assert!(estimate_memory_bytes(101, 50, 1024) > base);
+        assert!(estimate_memory_bytes(100, 51, 1024) > base);
+        assert!(estimate_memory_bytes(100, 50, 1026) > base);
+
+        let max = estimate_memory_bytes(u16::MAX, u16::MAX, u32::MAX);
+        assert!(max >= 2 * u16::MAX as u64 * u32::MAX as u64);
+    }
+}
@@ -0,0 +1,151 @@
+//! Plain Rust API for non-browser hosts.
+//!
+//! Same as [`crate::encode`] and [`crate::decode`], but operating on byte slices
+//! instead of JS arrays, so the library can be used (and tested) natively.
+
+use crate::{rs_decode, rs_encode, FlatShards, RsError, RsErrorCode, Shards};
+
+/// Compute `recovery_count` recovery shards of `originals`.
+///
+/// `originals` are the original shards (`shard_bytes` each) concatenated in order.
+/// The result contains all recovery shards concatenated in order.
+pub fn encode_bytes(
+    recovery_count: usize,
+    shard_bytes: usize,
+    originals: &[u8],
+) -> Result<Vec<u8>, RsError> {
+    let shards = FlatShards {
+        shard_len: checked_shard_len(shard_bytes, originals.len())?,
+        data: originals,
+    };
+
+    Ok(rs_encode(recovery_count, shard_bytes, shards)?.data)
+}
+
+/// Restore missing original shards.
+///
+/// `shards` are the received shards (`shard_bytes` each) concatenated,
+/// `indices` are their shard indices (recovery shards start at `original_count`).
+/// The result contains indices of the restored original shards and their data
+/// (concatenated in the same order).
+pub fn decode_bytes(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: &[u8],
+    indices: &[u16],
+) -> Result<(Vec<u16>, Vec<u8>), RsError> {
+    let shard_len = checked_shard_len(shard_bytes, shards.len())?;
+    if shards.len() / shard_bytes != indices.len() {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            format!(
+                "number of shards ({}) does not match the number of indices ({})",
+                shards.len() / shard_bytes,
+                indices.len()
+            ),
+        ));
+    }
+
+    let shards = IndexedShards {
+        indices,
+        shards: FlatShards {
+            shard_len,
+            data: shards,
+        },
+    };
+    let result = rs_decode(original_count, recovery_count, shard_bytes, shards)?;
+
+    Ok((result.indices.unwrap_or_default(), result.data))
+}
+
+/// Validate that `data_len` bytes can be split into shards of `shard_bytes`.
+fn checked_shard_len(shard_bytes: usize, data_len: usize) -> Result<u32, RsError> {
+    let shard_len = u32::try_from(shard_bytes)
+        .ok()
+        .filter(|shard_len| *shard_len > 0)
+        .ok_or_else(|| {
+            RsError::new(
+                RsErrorCode::InvalidShardSize,
+                format!("shard_bytes ({}) is out of range", shard_bytes),
+            )
+        })?;
+    if data_len % shard_bytes != 0 {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            format!(
+                "data length ({}) is not a multiple of shard_bytes ({})",
+                data_len, shard_bytes
+            ),
+        ));
+    }
+
+    Ok(shard_len)
+}
+
+/// Borrowed shards with explicit indices.
+struct IndexedShards<'a> {
+    indices: &'a [u16],
+    shards: FlatShards<'a>,
+}
+
+impl Shards for IndexedShards<'_> {
+    fn count(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn shard_len(&self) -> u32 {
+        self.shards.shard_len
+    }
+
+    fn index_at(&self, index: usize) -> u16 {
+        self.indices[index]
+    }
+
+    fn read_chunk<'a>(&'a self, index: usize, scratch: &'a mut Vec<u8>) -> &'a [u8] {
+        self.shards.read_chunk(index, scratch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARD: usize = 64;
+
+    fn originals() -> Vec<u8> {
+        (0..4 * SHARD).map(|i| (i * 3 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn should_encode_and_decode_bytes() {
+        let originals = originals();
+
+        let recovery = encode_bytes(3, SHARD, &originals).unwrap();
+        assert_eq!(recovery.len(), 3 * SHARD);
+
+        // originals 0 and 3 are lost
+        let mut shards = originals[SHARD..3 * SHARD].to_vec();
+        shards.extend(&recovery[2 * SHARD..]);
+        shards.extend(&recovery[..SHARD]);
+        let (indices, restored) = decode_bytes(4, 3, SHARD, &shards, &[1, 2, 6, 4]).unwrap();
+
+        assert_eq!(indices, vec![0, 3]);
+        assert_eq!(&restored[..SHARD], &originals[..SHARD]);
+        assert_eq!(&restored[SHARD..], &originals[3 * SHARD..]);
+    }
+
+    #[test]
+    fn should_reject_malformed_bytes() {
+        let originals = originals();
+
+        let err = encode_bytes(3, SHARD, &originals[1..]).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+
+        let err = encode_bytes(3, 0, &originals).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardSize);
+
+        let err = decode_bytes(4, 3, SHARD, &originals, &[0, 1, 2]).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+}
@@ -0,0 +1,183 @@
+//! Best-effort decoding of incomplete sets of shards.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys;
+
+use crate::plan::rs_missing_original_indices;
+use crate::{rs_decode_full, RsError, RsErrorCode, RsShardsCollection, Shards, ShardsCollection};
+
+/// Result of [`decode_partial`].
+///
+/// NOTE the object holds WASM memory, so make sure to call `free()`
+/// on the JS side once it's not needed anymore.
+#[wasm_bindgen]
+pub struct PartialDecode {
+    shards: Option<ShardsCollection>,
+    missing: Vec<u16>,
+}
+
+#[wasm_bindgen]
+impl PartialDecode {
+    /// Extract the original shards available after decoding.
+    ///
+    /// NOTE that subsequent calls to that method will return `None`.
+    #[wasm_bindgen]
+    pub fn take_shards(&mut self) -> Option<ShardsCollection> {
+        self.shards.take()
+    }
+
+    /// Sorted indices of original shards which could not be restored.
+    #[wasm_bindgen(getter)]
+    pub fn missing_indices(&self) -> js_sys::Uint16Array {
+        self.missing.as_slice().into()
+    }
+
+    /// Whether all of the original shards are available.
+    #[wasm_bindgen(getter)]
+    pub fn complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Restore as many original shards as possible.
+///
+/// If there are enough `shards` the result is [`crate::decode_full`]'s, i.e. all
+/// original shards in order. Otherwise, instead of failing, the result contains
+/// the original shards present in `shards` (sorted by index) and lists
+/// the indices of the missing ones.
+/// Other errors (e.g. invalid shard indices) are still reported.
+#[wasm_bindgen]
+pub fn decode_partial(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<PartialDecode, RsError> {
+    let (shards, missing) = rs_decode_partial(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    Ok(PartialDecode {
+        shards: Some(shards.into()),
+        missing,
+    })
+}
+
+fn rs_decode_partial(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<(RsShardsCollection, Vec<u16>), RsError> {
+    match rs_decode_full(original_count, recovery_count, shard_bytes, &shards) {
+        Ok(decoded) => Ok((decoded, vec![])),
+        Err(e) if e.code() == RsErrorCode::NotEnoughShards => {
+            let present = present_originals(original_count, &shards);
+            let missing = rs_missing_original_indices(
+                original_count,
+                present.indices.as_deref().unwrap_or_default(),
+            );
+
+            Ok((present, missing))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Copy original shards of `shards` into a collection sorted by index.
+fn present_originals(original_count: usize, shards: &impl Shards) -> RsShardsCollection {
+    let mut positions: Vec<usize> = (0..shards.count())
+        .filter(|i| (shards.index_at(*i) as usize) < original_count)
+        .collect();
+    positions.sort_by_key(|i| shards.index_at(*i));
+
+    let mut data = Vec::with_capacity(positions.len() * shards.shard_len() as usize);
+    let mut scratch = vec![];
+    for position in &positions {
+        data.extend(shards.read_chunk(*position, &mut scratch));
+    }
+
+    RsShardsCollection {
+        length: positions.len(),
+        shard_len: shards.shard_len(),
+        data,
+        indices: Some(positions.iter().map(|i| shards.index_at(*i)).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs_encode;
+
+    const SHARD: usize = 64;
+
+    fn originals() -> RsShardsCollection {
+        RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data: (0..4 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect(),
+            indices: None,
+        }
+    }
+
+    #[test]
+    fn should_return_present_originals_when_short_of_shards() {
+        let recovery = rs_encode(2, SHARD, originals()).unwrap();
+        let mut data = vec![];
+        data.extend(recovery.chunk_at(1));
+        data.extend([3u8; SHARD]);
+        data.extend([1u8; SHARD]);
+        let shards = RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data,
+            indices: Some(vec![5, 2, 0]),
+        };
+
+        let (partial, missing) = rs_decode_partial(4, 2, SHARD, shards).unwrap();
+
+        assert_eq!(missing, vec![1, 3]);
+        assert_eq!(partial.indices, Some(vec![0, 2]));
+        assert_eq!(partial.chunk_at(0), &[1u8; SHARD]);
+        assert_eq!(partial.chunk_at(1), &[3u8; SHARD]);
+    }
+
+    #[test]
+    fn should_return_all_originals_when_decodable() {
+        let recovery = rs_encode(2, SHARD, originals()).unwrap();
+        let mut data = vec![];
+        data.extend(recovery.chunk_at(0));
+        data.extend(recovery.chunk_at(1));
+        data.extend([2u8; SHARD]);
+        data.extend([4u8; SHARD]);
+        let shards = RsShardsCollection {
+            length: 4,
+            shard_len: SHARD as u32,
+            data,
+            indices: Some(vec![4, 5, 1, 3]),
+        };
+
+        let (decoded, missing) = rs_decode_partial(4, 2, SHARD, shards).unwrap();
+
+        assert!(missing.is_empty());
+        assert_eq!(decoded.data, originals().data);
+    }
+
+    #[test]
+    fn should_still_report_invalid_shards() {
+        let shards = RsShardsCollection {
+            length: 1,
+            shard_len: SHARD as u32,
+            data: vec![0; SHARD],
+            indices: Some(vec![6]),
+        };
+
+        let err = rs_decode_partial(4, 2, SHARD, shards).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::InvalidRecoveryShardIndex);
+    }
+}
@@ -0,0 +1,609 @@
+//! Protecting arbitrary payloads, which are not split into shards yet.
+//!
+//! By default the payload is split into consecutive chunks. With `interleave`
+//! the payload is seen as a row-major matrix with `original_count` columns
+//! and every original shard is one column of it (i.e. byte `i` goes to shard
+//! `i % original_count`), so a burst of lost or corrupted bytes is spread
+//! over many shards instead of wiping out a few.
+//!
+//! The unused bytes of the last shard(s) are filled with `pad_byte` (zero by default).
+//! They are stripped on recovery based on the recorded payload length,
+//! so the value only matters for tools inspecting the shards directly.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys;
+
+use crate::limits::{MAX_SHARD_BYTES, MIN_SHARD_BYTES, SHARD_BYTES_MULTIPLE};
+use crate::{
+    rs_decode_full, rs_encode_systematic, shards_len, validate_shard_bytes, CodingConfig, RsError,
+    RsErrorCode, RsShardsCollection, Shards, ShardsCollection,
+};
+
+/// Split `data` into original shards and compute `recovery_count` recovery shards.
+///
+/// The payload is padded (with `pad_byte`, zero by default) to a multiple of `shard_bytes`.
+/// The result contains all original shards followed by all recovery shards
+/// (with indices `0..original_count + recovery_count`) and records the payload
+/// length (`payload_len`), which should be passed to [`recover`] to strip the padding,
+/// as well as the coding parameters (`config`).
+/// If `interleave` is set, the payload is interleaved across the shards
+/// (see the module docs) and the same flag must be passed to [`recover`].
+#[wasm_bindgen]
+pub fn protect(
+    data: js_sys::Uint8Array,
+    shard_bytes: u32,
+    recovery_count: u16,
+    interleave: Option<bool>,
+    pad_byte: Option<u8>,
+) -> Result<ShardsCollection, RsError> {
+    let payload_len = data.length();
+    let result = rs_protect(
+        &data.to_vec(),
+        shard_bytes,
+        recovery_count as usize,
+        interleave.unwrap_or(false),
+        pad_byte.unwrap_or(0),
+    )?;
+
+    let original_count = (result.length - recovery_count as usize) as u16;
+    let mut result: ShardsCollection = result.into();
+    result.payload_len = Some(payload_len);
+    result.config = Some(CodingConfig {
+        original_count,
+        recovery_count,
+        shard_bytes,
+    });
+
+    Ok(result)
+}
+
+/// Reconstruct the payload protected with [`protect`].
+///
+/// `shards` may contain any subset (at least `original_count`) of the shards returned by `protect`.
+/// `original_byte_len` is the length of the protected payload (`payload_len`).
+/// The result is the exact payload, i.e. original shards concatenated in order
+/// (or re-interleaved, if `interleave` is set) with the padding removed.
+#[wasm_bindgen]
+pub fn recover(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    original_byte_len: u32,
+    shards: ShardsCollection,
+    interleave: Option<bool>,
+) -> Result<js_sys::Uint8Array, RsError> {
+    let result = rs_recover(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        original_byte_len as usize,
+        shards,
+        interleave.unwrap_or(false),
+    )?;
+
+    Ok(result.as_slice().into())
+}
+
+/// The smallest valid `shard_bytes` to split `payload_len` bytes into `original_count` shards.
+///
+/// The result satisfies the shard size constraints (see `min_shard_bytes` and
+/// `shard_bytes_multiple`). Fails if the shards would have to be too large.
+#[wasm_bindgen]
+pub fn shard_bytes_for(payload_len: usize, original_count: u16) -> Result<u32, RsError> {
+    rs_shard_bytes_for(payload_len, original_count as usize)
+}
+
+/// The largest payload (in bytes) `original_count` shards of `shard_bytes` can hold.
+///
+/// NOTE the result is a `BigInt` on the JS side, since it may not fit into `u32`.
+#[wasm_bindgen]
+pub fn max_payload_bytes(original_count: u16, shard_bytes: u32) -> u64 {
+    original_count as u64 * shard_bytes as u64
+}
+
+/// Number of padding bytes filling a payload of `payload_len` bytes to `original_count` shards.
+///
+/// That's the number of trailing bytes to strip from the original shards
+/// (concatenated) to get the payload back. Fails if the payload doesn't fit.
+#[wasm_bindgen]
+pub fn padding_len(
+    payload_len: usize,
+    original_count: u16,
+    shard_bytes: u32,
+) -> Result<usize, RsError> {
+    rs_padding_len(payload_len, original_count as usize, shard_bytes as usize)
+}
+
+fn rs_padding_len(
+    payload_len: usize,
+    original_count: usize,
+    shard_bytes: usize,
+) -> Result<usize, RsError> {
+    let capacity = shards_len(
+        original_count,
+        shard_bytes,
+        RsErrorCode::InvalidPayloadLength,
+    )?;
+    capacity.checked_sub(payload_len).ok_or_else(|| {
+        RsError::new(
+            RsErrorCode::InvalidPayloadLength,
+            format!(
+                "payload length ({}) exceeds the size of original shards ({})",
+                payload_len, capacity
+            ),
+        )
+    })
+}
+
+/// Split `data` into exactly `original_count` original shards, ready to be encoded.
+///
+/// The shard size is picked with [`shard_bytes_for`] (see `shard_len` of the result)
+/// and the payload is padded (with `pad_byte`, zero by default) to fill all of the shards. The payload length
+/// is recorded in the result (`payload_len`), pass it to [`recover`] to strip the padding.
+/// If `interleave` is set, the payload is interleaved across the shards (see the module docs).
+#[wasm_bindgen]
+pub fn split_payload(
+    data: js_sys::Uint8Array,
+    original_count: u16,
+    interleave: Option<bool>,
+    pad_byte: Option<u8>,
+) -> Result<ShardsCollection, RsError> {
+    let payload_len = data.length();
+    let result = rs_split_payload(
+        &data.to_vec(),
+        original_count as usize,
+        interleave.unwrap_or(false),
+        pad_byte.unwrap_or(0),
+    )?;
+
+    let mut result: ShardsCollection = result.into();
+    result.payload_len = Some(payload_len);
+
+    Ok(result)
+}
+
+/// Split the contents of `file` into `original_count` original shards without reading it whole.
+///
+/// The blob is read one shard at a time and `on_shard(index, shard)` is called with
+/// every original shard (in order), so the shards can be e.g. added to an `Encoder`
+/// or sent out as they are read. The final shard(s) are zero-padded.
+/// Returns a `Promise` resolving once all of the shards were delivered.
+/// Fails if the file doesn't fit into the shards (see [`padding_len`]),
+/// can't be read or the callback throws.
+#[wasm_bindgen]
+pub async fn split_file(
+    file: web_sys::Blob,
+    shard_bytes: u32,
+    original_count: u16,
+    on_shard: js_sys::Function,
+) -> Result<(), RsError> {
+    validate_shard_bytes(shard_bytes as usize)?;
+    rs_padding_len(
+        file.size() as usize,
+        original_count as usize,
+        shard_bytes as usize,
+    )?;
+
+    let read_failed = |e: JsValue| {
+        RsError::new(
+            RsErrorCode::ReadFailed,
+            format!("Failed to read the file: {:?}", e),
+        )
+    };
+    for index in 0..original_count {
+        let begin = index as f64 * shard_bytes as f64;
+        let end = (begin + shard_bytes as f64).min(file.size());
+        let shard = js_sys::Uint8Array::new_with_length(shard_bytes);
+        if begin < end {
+            let slice = file
+                .slice_with_f64_and_f64(begin, end)
+                .map_err(read_failed)?;
+            let buffer = JsFuture::from(slice.array_buffer())
+                .await
+                .map_err(read_failed)?;
+            shard.set(&js_sys::Uint8Array::new(&buffer), 0);
+        }
+
+        on_shard
+            .call2(&JsValue::NULL, &JsValue::from(index), &shard)
+            .map_err(|e| {
+                RsError::new(
+                    RsErrorCode::CallbackFailed,
+                    format!("Shard callback failed: {:?}", e),
+                )
+            })?;
+    }
+
+    Ok(())
+}
+
+fn rs_split_payload(
+    data: &[u8],
+    original_count: usize,
+    interleave: bool,
+    pad_byte: u8,
+) -> Result<RsShardsCollection, RsError> {
+    let shard_bytes = rs_shard_bytes_for(data.len(), original_count)?;
+
+    Ok(RsShardsCollection {
+        length: original_count,
+        shard_len: shard_bytes,
+        data: split(
+            data,
+            original_count,
+            shard_bytes as usize,
+            interleave,
+            pad_byte,
+        )?,
+        indices: None,
+    })
+}
+
+/// Pad `data` with `pad_byte` to `original_count` shards and lay it out as shards.
+fn split(
+    data: &[u8],
+    original_count: usize,
+    shard_bytes: usize,
+    interleave: bool,
+    pad_byte: u8,
+) -> Result<Vec<u8>, RsError> {
+    let len = shards_len(
+        original_count,
+        shard_bytes,
+        RsErrorCode::InvalidPayloadLength,
+    )?;
+    let mut padded = data.to_vec();
+    padded.resize(len, pad_byte);
+    if !interleave {
+        return Ok(padded);
+    }
+
+    let mut shards = vec![0; padded.len()];
+    for (i, byte) in padded.into_iter().enumerate() {
+        shards[(i % original_count) * shard_bytes + i / original_count] = byte;
+    }
+    Ok(shards)
+}
+
+/// Reverse of [`split`] (without stripping the padding).
+fn join(shards: Vec<u8>, original_count: usize, shard_bytes: usize, interleave: bool) -> Vec<u8> {
+    if !interleave {
+        return shards;
+    }
+
+    let mut data = vec![0; shards.len()];
+    for (i, byte) in data.iter_mut().enumerate() {
+        *byte = shards[(i % original_count) * shard_bytes + i / original_count];
+    }
+    data
+}
+
+fn rs_shard_bytes_for(payload_len: usize, original_count: usize) -> Result<u32, RsError> {
+    if original_count == 0 {
+        return Err(RsError::new(
+            RsErrorCode::UnsupportedShardCount,
+            "original_count must be at least 1",
+        ));
+    }
+
+    // computed with 64-bit arithmetic, so rounding up can't overflow
+    let multiple = SHARD_BYTES_MULTIPLE as u64;
+    let shard_bytes = (payload_len as u64)
+        .div_ceil(original_count as u64)
+        .max(MIN_SHARD_BYTES as u64)
+        .div_ceil(multiple)
+        * multiple;
+    if shard_bytes > MAX_SHARD_BYTES as u64 {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardSize,
+            format!(
+                "payload of {} bytes needs shards of {} bytes, which exceeds the maximum ({})",
+                payload_len, shard_bytes, MAX_SHARD_BYTES
+            ),
+        ));
+    }
+
+    Ok(shard_bytes as u32)
+}
+
+fn rs_protect(
+    data: &[u8],
+    shard_bytes: u32,
+    recovery_count: usize,
+    interleave: bool,
+    pad_byte: u8,
+) -> Result<RsShardsCollection, RsError> {
+    if shard_bytes == 0 {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardSize,
+            "shard_bytes must be greater than 0",
+        ));
+    }
+
+    let shard_len = shard_bytes as usize;
+    let original_count = data.len().div_ceil(shard_len);
+    let shards = RsShardsCollection {
+        length: original_count,
+        shard_len: shard_bytes,
+        data: split(data, original_count, shard_len, interleave, pad_byte)?,
+        indices: None,
+    };
+
+    rs_encode_systematic(recovery_count, shard_len, shards)
+}
+
+fn rs_recover(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    payload_len: usize,
+    shards: impl Shards,
+    interleave: bool,
+) -> Result<Vec<u8>, RsError> {
+    rs_padding_len(payload_len, original_count, shard_bytes)?;
+
+    if shards.count() < original_count {
+        return Err(RsError::not_enough_shards(
+            shards.count(),
+            original_count,
+            format!(
+                "not enough shards to recover the payload: got {}, need at least {}",
+                shards.count(),
+                original_count
+            ),
+        ));
+    }
+
+    let decoded = rs_decode_full(original_count, recovery_count, shard_bytes, shards)?;
+    let mut data = join(decoded.data, original_count, shard_bytes, interleave);
+    data.truncate(payload_len);
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs_encode;
+
+    const SHARD: usize = 64;
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Build a collection out of shards at given `positions` of `shards`.
+    fn pick(shards: &RsShardsCollection, positions: &[usize]) -> RsShardsCollection {
+        let mut data = vec![];
+        let mut indices = vec![];
+        for position in positions {
+            data.extend(shards.chunk_at(*position));
+            indices.push(shards.chunk_index_at(*position));
+        }
+
+        RsShardsCollection {
+            length: positions.len(),
+            shard_len: shards.shard_len,
+            data,
+            indices: Some(indices),
+        }
+    }
+
+    #[test]
+    fn should_protect_and_recover_unaligned_payload() {
+        let data = payload(150);
+
+        let protected = rs_protect(&data, SHARD as u32, 2, false, 0).unwrap();
+
+        assert_eq!(protected.length, 5);
+        assert_eq!(protected.indices, Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(&protected.data[..150], &data[..]);
+        assert_eq!(&protected.data[150..3 * SHARD], &[0u8; 3 * SHARD - 150]);
+
+        let recovered =
+            rs_recover(3, 2, SHARD, data.len(), pick(&protected, &[1, 3, 4]), false).unwrap();
+
+        assert_eq!(recovered, data);
+    }
+
+    #[test]
+    fn should_recover_payload_after_dropping_shards() {
+        let data = payload(1000);
+        let protected = rs_protect(&data, SHARD as u32, 6, false, 0).unwrap();
+        assert_eq!(protected.length, 22);
+
+        let kept: Vec<usize> = (0..22).filter(|p| ![0, 5, 6, 15, 17].contains(p)).collect();
+        let recovered = rs_recover(16, 6, SHARD, 1000, pick(&protected, &kept), false).unwrap();
+
+        assert_eq!(recovered, data);
+    }
+
+    #[test]
+    fn should_not_recover_with_too_few_shards() {
+        let data = payload(1000);
+        let protected = rs_protect(&data, SHARD as u32, 6, false, 0).unwrap();
+
+        let kept: Vec<usize> = (7..22).collect();
+        let err = rs_recover(16, 6, SHARD, 1000, pick(&protected, &kept), false).unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::NotEnoughShards);
+        assert_eq!((err.have(), err.need()), (Some(15), Some(16)));
+        assert_eq!(
+            err.message(),
+            "not enough shards to recover the payload: got 15, need at least 16"
+        );
+    }
+
+    #[test]
+    fn should_split_payload_and_recover_it() {
+        let data = payload(500);
+
+        let originals = rs_split_payload(&data, 4, false, 0).unwrap();
+        assert_eq!(originals.length, 4);
+        assert_eq!(originals.shard_len, 126);
+        assert_eq!(&originals.data[..500], &data[..]);
+        assert_eq!(&originals.data[500..], &[0; 4]);
+
+        let recovery = rs_encode(2, 126, &originals).unwrap();
+        let mut shards = pick(&originals, &[0, 3]);
+        shards.length += 2;
+        shards.data.extend(&recovery.data);
+        shards.indices.as_mut().unwrap().extend([4, 5]);
+
+        let recovered = rs_recover(4, 2, 126, 500, shards, false).unwrap();
+        assert_eq!(recovered, data);
+    }
+
+    #[test]
+    fn should_pick_shard_bytes_for_payload() {
+        // fits exactly
+        assert_eq!(rs_shard_bytes_for(16 * SHARD, 16), Ok(SHARD as u32));
+        // needs rounding up to the next whole shard...
+        assert_eq!(rs_shard_bytes_for(16 * SHARD + 1, 16), Ok(SHARD as u32 + 2));
+        // ...and to the required multiple
+        assert_eq!(rs_shard_bytes_for(3 * 7, 3), Ok(8));
+        // tiny payloads still need minimal shards
+        assert_eq!(rs_shard_bytes_for(0, 4), Ok(2));
+        assert_eq!(rs_shard_bytes_for(1, 4), Ok(2));
+
+        let largest = MAX_SHARD_BYTES;
+        assert_eq!(rs_shard_bytes_for(largest, 1), Ok(MAX_SHARD_BYTES as u32));
+        let err = rs_shard_bytes_for(largest + 1, 1).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidShardSize);
+
+        let err = rs_shard_bytes_for(100, 0).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::UnsupportedShardCount);
+    }
+
+    #[test]
+    fn should_recover_interleaved_payload_after_burst_error() {
+        let data = payload(1000);
+        let mut protected = rs_protect(&data, SHARD as u32, 4, true, 0).unwrap();
+        assert_eq!(protected.length, 20);
+        // byte `i` lands in shard `i % 16` at offset `i / 16`
+        assert_eq!(protected.chunk_at(3)[2], data[2 * 16 + 3]);
+
+        // corrupt a contiguous run of 4 bytes of the payload spanning shards 14, 15, 0 and 1
+        let burst = 494..498;
+        let mut hit: Vec<usize> = burst.clone().map(|i| i % 16).collect();
+        hit.sort();
+        assert_eq!(hit, vec![0, 1, 14, 15]);
+        for i in burst {
+            protected.data[(i % 16) * SHARD + i / 16] ^= 0xff;
+        }
+
+        // each corrupted shard is dropped (e.g. detected by a checksum) and restored
+        let kept: Vec<usize> = (0..20).filter(|p| !hit.contains(p)).collect();
+        let recovered = rs_recover(16, 4, SHARD, 1000, pick(&protected, &kept), true).unwrap();
+        assert_eq!(recovered, data);
+    }
+
+    #[test]
+    fn should_split_interleaved_payload() {
+        let data = payload(10);
+
+        let originals = rs_split_payload(&data, 3, true, 0).unwrap();
+        assert_eq!(originals.shard_len, 4);
+        assert_eq!(originals.data, vec![0, 3, 6, 9, 1, 4, 7, 0, 2, 5, 8, 0]);
+
+        let recovered = rs_recover(3, 1, 4, 10, originals, true).unwrap();
+        assert_eq!(recovered, data);
+    }
+
+    #[test]
+    fn should_pad_with_given_byte() {
+        let data = payload(150);
+        let protected = rs_protect(&data, SHARD as u32, 2, false, 0xff).unwrap();
+        assert_eq!(protected.length, 5);
+        assert_eq!(&protected.data[..150], &data[..]);
+        assert!(protected.data[150..3 * SHARD].iter().all(|b| *b == 0xff));
+
+        // the padding is still stripped on recovery
+        let recovered = rs_recover(3, 2, SHARD, 150, pick(&protected, &[4, 1, 3]), false).unwrap();
+        assert_eq!(recovered, data);
+
+        // interleaved padding ends up at the tail of the last shards
+        let originals = rs_split_payload(&data[..10], 3, true, 0xff).unwrap();
+        assert_eq!(
+            originals.data,
+            vec![0, 3, 6, 9, 1, 4, 7, 0xff, 2, 5, 8, 0xff]
+        );
+    }
+
+    #[test]
+    fn should_compute_max_payload_bytes() {
+        assert_eq!(max_payload_bytes(16, SHARD as u32), 16 * SHARD as u64);
+        assert_eq!(max_payload_bytes(0, SHARD as u32), 0);
+        assert_eq!(
+            max_payload_bytes(u16::MAX, u32::MAX),
+            u16::MAX as u64 * u32::MAX as u64
+        );
+    }
+
+    #[test]
+    fn should_compute_padding_len() {
+        assert_eq!(rs_padding_len(4 * SHARD, 4, SHARD), Ok(0));
+        assert_eq!(rs_padding_len(150, 3, SHARD), Ok(3 * SHARD - 150));
+        assert_eq!(rs_padding_len(0, 3, SHARD), Ok(3 * SHARD));
+
+        let err = rs_padding_len(4 * SHARD + 1, 4, SHARD).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidPayloadLength);
+
+        let err = rs_padding_len(0, usize::MAX / 2 + 1, 2).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidPayloadLength);
+        let err = split(&[], usize::MAX / 2 + 1, 2, false, 0).unwrap_err();
+        assert_eq!(err.code(), RsErrorCode::InvalidPayloadLength);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const SHARD: usize = 64;
+
+    #[wasm_bindgen_test]
+    async fn should_split_file_shard_by_shard() {
+        let data: Vec<u8> = (0..150).map(|i| i as u8).collect();
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data.as_slice()));
+        let file = web_sys::Blob::new_with_u8_array_sequence(&parts).unwrap();
+
+        let received = Rc::new(RefCell::new(vec![]));
+        let on_shard = Closure::<dyn FnMut(u16, js_sys::Uint8Array)>::new({
+            let received = received.clone();
+            move |index, shard: js_sys::Uint8Array| {
+                received.borrow_mut().push((index, shard.to_vec()));
+            }
+        });
+
+        split_file(
+            file,
+            SHARD as u32,
+            4,
+            on_shard
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        )
+        .await
+        .unwrap();
+
+        let received = received.borrow();
+        assert_eq!(received.len(), 4);
+        let indices: Vec<u16> = received.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3]);
+        let shards: Vec<u8> = received
+            .iter()
+            .flat_map(|(_, shard)| shard.clone())
+            .collect();
+        assert_eq!(shards, split(&data, 4, SHARD, false, 0).unwrap());
+    }
+}
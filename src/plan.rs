@@ -0,0 +1,154 @@
+//! Helpers answering questions about a set of shards before decoding it.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys;
+
+/// Check whether given set of shards is sufficient to restore all originals.
+///
+/// `present_indices` are absolute shard indices (recovery shards start at `original_count`).
+/// Duplicated indices are counted only once and the function returns `false`
+/// if any of the indices is out of range.
+#[wasm_bindgen]
+pub fn can_decode(
+    original_count: u16,
+    recovery_count: u16,
+    present_indices: js_sys::Uint16Array,
+) -> bool {
+    rs_can_decode(
+        original_count as usize,
+        recovery_count as usize,
+        &present_indices.to_vec(),
+    )
+}
+
+fn rs_can_decode(original_count: usize, recovery_count: usize, present_indices: &[u16]) -> bool {
+    let mut seen = vec![false; original_count + recovery_count];
+    let mut distinct = 0;
+    for idx in present_indices {
+        match seen.get_mut(*idx as usize) {
+            Some(true) => {}
+            Some(slot) => {
+                *slot = true;
+                distinct += 1;
+            }
+            None => return false,
+        }
+    }
+
+    distinct >= original_count
+}
+
+/// List original indices (in `0..original_count`) that are not present.
+///
+/// The result is sorted. Recovery shard indices (`>= original_count`) in `present_indices`
+/// don't cover any particular original, but every distinct recovery shard can fill one gap,
+/// so the number of additional shards (of any kind) required to decode is
+/// the length of the result minus the number of distinct recovery shards held.
+#[wasm_bindgen]
+pub fn missing_original_indices(
+    original_count: u16,
+    present_indices: js_sys::Uint16Array,
+) -> js_sys::Uint16Array {
+    let missing = rs_missing_original_indices(original_count as usize, &present_indices.to_vec());
+
+    missing.as_slice().into()
+}
+
+pub(crate) fn rs_missing_original_indices(
+    original_count: usize,
+    present_indices: &[u16],
+) -> Vec<u16> {
+    let mut present = vec![false; original_count];
+    for idx in present_indices {
+        if let Some(slot) = present.get_mut(*idx as usize) {
+            *slot = true;
+        }
+    }
+
+    (0..original_count)
+        .filter(|idx| !present[*idx])
+        .map(|idx| idx as u16)
+        .collect()
+}
+
+/// Minimal number of shards to keep so that decoding survives `tolerable_losses` more losses.
+///
+/// Any `original_count` distinct shards are enough to decode, so that's
+/// `original_count + tolerable_losses`, clamped to the total number of shards
+/// (keeping all of them can't tolerate more losses than there are recovery shards).
+#[wasm_bindgen]
+pub fn minimal_shards_to_keep(
+    original_count: u16,
+    recovery_count: u16,
+    tolerable_losses: u16,
+) -> u32 {
+    let total = original_count as u32 + recovery_count as u32;
+
+    (original_count as u32 + tolerable_losses as u32).min(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_with_just_enough_shards() {
+        assert!(rs_can_decode(3, 2, &[0, 3, 4]));
+        assert!(rs_can_decode(3, 2, &[0, 1, 2]));
+    }
+
+    #[test]
+    fn should_not_decode_with_one_shard_short() {
+        assert!(!rs_can_decode(3, 2, &[0, 4]));
+    }
+
+    #[test]
+    fn should_count_duplicates_once() {
+        assert!(!rs_can_decode(3, 2, &[0, 4, 4]));
+        assert!(rs_can_decode(3, 2, &[0, 4, 4, 1]));
+    }
+
+    #[test]
+    fn should_reject_out_of_range_indices() {
+        assert!(!rs_can_decode(3, 2, &[0, 1, 2, 5]));
+    }
+
+    #[test]
+    fn should_list_no_missing_originals() {
+        assert_eq!(
+            rs_missing_original_indices(3, &[2, 0, 1]),
+            Vec::<u16>::new()
+        );
+    }
+
+    #[test]
+    fn should_list_some_missing_originals() {
+        assert_eq!(rs_missing_original_indices(5, &[4, 1, 6, 1]), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn should_list_all_missing_originals() {
+        assert_eq!(rs_missing_original_indices(3, &[3, 4]), vec![0, 1, 2]);
+        assert_eq!(rs_missing_original_indices(3, &[]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn should_keep_originals_without_tolerable_losses() {
+        assert_eq!(minimal_shards_to_keep(10, 4, 0), 10);
+    }
+
+    #[test]
+    fn should_keep_extra_shard_per_tolerable_loss() {
+        assert_eq!(minimal_shards_to_keep(10, 4, 1), 11);
+        assert_eq!(minimal_shards_to_keep(10, 4, 4), 14);
+    }
+
+    #[test]
+    fn should_clamp_shards_to_keep_to_total() {
+        assert_eq!(minimal_shards_to_keep(10, 4, 5), 14);
+        assert_eq!(
+            minimal_shards_to_keep(u16::MAX, u16::MAX, u16::MAX),
+            2 * u16::MAX as u32
+        );
+    }
+}
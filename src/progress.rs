@@ -0,0 +1,312 @@
+//! Long-running operations, which report their progress or yield to the event loop.
+
+use reed_solomon::ReedSolomonEncoder;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys;
+
+use crate::bench::performance;
+use crate::{
+    add_original_at, check_encoder_input, prepare_decoder_with, prepare_encoder_with,
+    recovery_collection, restored_collection, RsError, RsErrorCode, RsShardsCollection, Shards,
+    ShardsCollection,
+};
+
+/// How many original shards are added between two progress reports.
+const PROGRESS_INTERVAL: usize = 256;
+
+/// How many original shards are added before yielding to the event loop.
+const YIELD_INTERVAL: usize = 256;
+
+/// How many shards are added between two checks of the deadline.
+const DEADLINE_INTERVAL: usize = 64;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &js_sys::Function, millis: i32) -> JsValue;
+}
+
+/// Same as [`crate::encode`], but reports progress to `on_progress`.
+///
+/// The callback receives the fraction (`0..1`) of original shards added to the encoder
+/// (every 256 shards) and `1` once recovery shards are computed.
+/// If the callback throws, encoding is aborted with an error.
+#[wasm_bindgen]
+pub fn encode_with_progress(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    on_progress: js_sys::Function,
+) -> Result<ShardsCollection, RsError> {
+    let result = rs_encode_with_progress(
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        |progress| {
+            on_progress
+                .call1(&JsValue::NULL, &JsValue::from_f64(progress))
+                .map(|_| ())
+                .map_err(|e| {
+                    RsError::new(
+                        RsErrorCode::CallbackFailed,
+                        format!("Progress callback failed: {:?}", e),
+                    )
+                })
+        },
+    )?;
+
+    Ok(result.into())
+}
+
+fn rs_encode_with_progress(
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    mut on_progress: impl FnMut(f64) -> Result<(), RsError>,
+) -> Result<RsShardsCollection, RsError> {
+    let original_count = shards.count();
+    let mut encoder = prepare_encoder_with(recovery_count, shard_bytes, &shards, |added| {
+        if added % PROGRESS_INTERVAL == 0 && added < original_count {
+            on_progress(added as f64 / original_count as f64)?;
+        }
+        Ok(())
+    })?;
+
+    let result = encoder.encode()?;
+    let recovery = recovery_collection(
+        original_count,
+        recovery_count,
+        shards.shard_len(),
+        result.recovery_iter(),
+    );
+    on_progress(1.0)?;
+
+    Ok(recovery)
+}
+
+/// Same as [`crate::encode`], but doesn't block the event loop for the whole time.
+///
+/// Returns a `Promise` resolving to the recovery shards. Original shards are added
+/// to the encoder in batches (of 256 shards) and the control is given back to
+/// the event loop (via `setTimeout`) after every batch, so that e.g. the UI stays responsive.
+/// NOTE that computing recovery shards (after all originals are added) is still done in one go.
+/// The `shards` must not be modified until the promise is settled.
+#[wasm_bindgen]
+pub async fn encode_async(
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<ShardsCollection, RsError> {
+    let recovery_count = recovery_count as usize;
+    check_encoder_input(recovery_count, shard_bytes as usize, &shards)?;
+    let original_count = shards.count();
+    let mut encoder =
+        ReedSolomonEncoder::new(original_count, recovery_count, shard_bytes as usize)?;
+
+    let mut scratch = vec![];
+    for i in 0..original_count {
+        if i > 0 && i % YIELD_INTERVAL == 0 {
+            yield_to_event_loop().await;
+        }
+        add_original_at(&mut encoder, &shards, i, &mut scratch)?;
+    }
+
+    let result = encoder.encode()?;
+    let recovery = recovery_collection(
+        original_count,
+        recovery_count,
+        shards.shard_len(),
+        result.recovery_iter(),
+    );
+
+    Ok(recovery.into())
+}
+
+/// Same as [`crate::decode`], but gives up once `deadline_ms` passes.
+///
+/// `deadline_ms` is a `performance.now()` timestamp. The deadline is checked while
+/// adding the shards to the decoder (every 64 shards) and right before restoring
+/// the missing ones, in which case the decoding fails with a `"decode timed out"` error.
+/// NOTE that restoring itself can't be interrupted, so a decode may still finish
+/// somewhat after the deadline.
+#[wasm_bindgen]
+pub fn decode_with_deadline(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+    deadline_ms: f64,
+) -> Result<ShardsCollection, RsError> {
+    let performance = performance()?;
+    let result = rs_decode_with_deadline(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        || performance.now() >= deadline_ms,
+    )?;
+
+    Ok(result.into())
+}
+
+fn rs_decode_with_deadline(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+    mut deadline_passed: impl FnMut() -> bool,
+) -> Result<RsShardsCollection, RsError> {
+    let mut check_deadline = || {
+        if deadline_passed() {
+            return Err(RsError::new(RsErrorCode::TimedOut, "decode timed out"));
+        }
+        Ok(())
+    };
+
+    let mut decoder = prepare_decoder_with(
+        original_count,
+        recovery_count,
+        shard_bytes,
+        &shards,
+        false,
+        |added| {
+            if added % DEADLINE_INTERVAL == 0 {
+                check_deadline()?;
+            }
+            Ok(())
+        },
+    )?;
+    check_deadline()?;
+
+    let decoding_result = decoder.decode()?;
+    Ok(restored_collection(
+        shards.shard_len(),
+        decoding_result.restored_original_iter(),
+    ))
+}
+
+/// Let the event loop run pending tasks (rendering, I/O) before continuing.
+async fn yield_to_event_loop() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        set_timeout(&resolve, 0);
+    });
+    // the promise is never rejected
+    let _ = JsFuture::from(promise).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs_encode;
+
+    const SHARD: usize = 64;
+
+    fn originals(count: usize) -> RsShardsCollection {
+        RsShardsCollection {
+            length: count,
+            shard_len: SHARD as u32,
+            data: (0..count * SHARD).map(|i| (i % 253) as u8).collect(),
+            indices: None,
+        }
+    }
+
+    #[test]
+    fn should_report_increasing_progress() {
+        let mut reported = vec![];
+
+        let encoded = rs_encode_with_progress(16, SHARD, originals(1000), |progress| {
+            reported.push(progress);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(reported, vec![0.256, 0.512, 0.768, 1.0]);
+        assert_eq!(
+            encoded.data,
+            rs_encode(16, SHARD, originals(1000)).unwrap().data
+        );
+    }
+
+    #[test]
+    fn should_time_out_while_adding_shards() {
+        let encoded = rs_encode(100, SHARD, originals(1000)).unwrap();
+        let mut checks = 0;
+
+        let err = rs_decode_with_deadline(1000, 100, SHARD, encoded, || {
+            checks += 1;
+            true
+        })
+        .unwrap_err();
+
+        assert_eq!(checks, 1);
+        assert_eq!(err.code(), RsErrorCode::TimedOut);
+        assert_eq!(err.message(), "decode timed out");
+    }
+
+    #[test]
+    fn should_decode_before_deadline() {
+        let data = originals(1000).data;
+        let mut shards = rs_encode(100, SHARD, originals(1000)).unwrap();
+        // append all originals but the first 100
+        shards.data.extend(&data[100 * SHARD..]);
+        shards.indices.as_mut().unwrap().extend(100..1000u16);
+        shards.length = 1000;
+
+        let decoded = rs_decode_with_deadline(1000, 100, SHARD, shards, || false).unwrap();
+
+        assert_eq!(decoded.length, 100);
+        assert_eq!(decoded.data, &data[..100 * SHARD]);
+    }
+
+    #[test]
+    fn should_abort_when_callback_fails() {
+        let mut calls = 0;
+
+        let err = rs_encode_with_progress(16, SHARD, originals(1000), |_| {
+            calls += 1;
+            Err(RsError::new(RsErrorCode::CallbackFailed, "stop"))
+        })
+        .unwrap_err();
+
+        assert_eq!(calls, 1);
+        assert_eq!(err.code(), RsErrorCode::CallbackFailed);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use crate::rs_encode;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const SHARD: usize = 64;
+
+    #[wasm_bindgen_test]
+    async fn should_encode_asynchronously() {
+        let data: Vec<u8> = (0..1000 * SHARD).map(|i| (i % 253) as u8).collect();
+        let shards =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+
+        let expected = rs_encode(16, SHARD, &shards).unwrap();
+        let encoded =
+            RsShardsCollection::from(encode_async(16, SHARD as u32, shards).await.unwrap());
+
+        assert_eq!(encoded.indices, expected.indices);
+        assert_eq!(encoded.data, expected.data);
+    }
+
+    #[wasm_bindgen_test]
+    fn should_time_out_with_past_deadline() {
+        let data: Vec<u8> = (0..3 * SHARD).map(|i| i as u8).collect();
+        let shards =
+            ShardsCollection::new(SHARD as u32, data.as_slice().into(), None, None).unwrap();
+
+        let err = decode_with_deadline(3, 2, SHARD as u32, shards, 0.0)
+            .err()
+            .unwrap();
+
+        assert_eq!(err.code(), RsErrorCode::TimedOut);
+    }
+}
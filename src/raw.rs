@@ -0,0 +1,127 @@
+//! Low-level API operating directly on WASM linear memory.
+//!
+//! Meant for integrators which already keep the shards in WASM memory
+//! (e.g. another Rust/WASM module), to avoid copying through `Uint8Array`s.
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{rs_encode_into, shards_len, FlatShards, RsError, RsErrorCode};
+
+/// Same as [`crate::encode_into`], but reads and writes raw WASM memory.
+///
+/// `data_ptr` points to `original_count` original shards (`shard_bytes` each)
+/// concatenated in order, `data_len` is their total length. All recovery shards
+/// (`recovery_count * shard_bytes` bytes) are written in order at `out_ptr`.
+/// Returns the number of bytes written.
+///
+/// # Safety
+///
+/// `data_ptr` must be valid for reads of `data_len` bytes, `out_ptr` must be valid
+/// for writes of `recovery_count * shard_bytes` bytes and the two regions must not overlap.
+/// Nothing is checked here, so prefer the `Uint8Array`-based functions
+/// if you're not sure what you're doing.
+#[wasm_bindgen]
+pub unsafe fn encode_ptr(
+    recovery_count: u16,
+    shard_bytes: u32,
+    original_count: u16,
+    data_ptr: *const u8,
+    data_len: usize,
+    out_ptr: *mut u8,
+) -> Result<u32, RsError> {
+    let expected = shards_len(
+        original_count as usize,
+        shard_bytes as usize,
+        RsErrorCode::InvalidShardsCollection,
+    )?;
+    if data_len != expected {
+        return Err(RsError::new(
+            RsErrorCode::InvalidShardsCollection,
+            format!(
+                "data_len ({}) does not match original_count * shard_bytes ({})",
+                data_len, expected
+            ),
+        ));
+    }
+
+    let shards = FlatShards {
+        shard_len: shard_bytes,
+        data: core::slice::from_raw_parts(data_ptr, data_len),
+    };
+    let output_len = shards_len(
+        recovery_count as usize,
+        shard_bytes as usize,
+        RsErrorCode::OutOfBounds,
+    )?;
+    let output = core::slice::from_raw_parts_mut(out_ptr, output_len);
+
+    let written = rs_encode_into(
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+        output_len,
+        |offset, chunk| output[offset..offset + chunk.len()].copy_from_slice(chunk),
+    )?;
+
+    Ok(written as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rs_encode, RsShardsCollection};
+
+    const SHARD: usize = 64;
+
+    #[test]
+    fn should_encode_linear_memory() {
+        let data: Vec<u8> = (0..5 * SHARD).map(|i| (i % 251) as u8).collect();
+        let mut out = vec![0u8; 3 * SHARD];
+
+        let written = unsafe {
+            encode_ptr(
+                3,
+                SHARD as u32,
+                5,
+                data.as_ptr(),
+                data.len(),
+                out.as_mut_ptr(),
+            )
+        }
+        .unwrap();
+
+        let expected = rs_encode(
+            3,
+            SHARD,
+            RsShardsCollection {
+                length: 5,
+                shard_len: SHARD as u32,
+                data: data.clone(),
+                indices: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(written as usize, out.len());
+        assert_eq!(out, expected.data);
+    }
+
+    #[test]
+    fn should_reject_mismatching_data_len() {
+        let data = vec![0u8; 5 * SHARD];
+        let mut out = vec![0u8; 3 * SHARD];
+
+        let err = unsafe {
+            encode_ptr(
+                3,
+                SHARD as u32,
+                4,
+                data.as_ptr(),
+                data.len(),
+                out.as_mut_ptr(),
+            )
+        }
+        .unwrap_err();
+
+        assert_eq!(err.code(), RsErrorCode::InvalidShardsCollection);
+    }
+}
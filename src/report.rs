@@ -0,0 +1,248 @@
+//! Decoding with a detailed account of the shards used.
+
+use reed_solomon::ReedSolomonDecoder;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use web_sys::js_sys;
+
+use crate::{
+    check_count, restored_collection, validate_shard_bytes, RsError, RsShardsCollection, Shards,
+    ShardsCollection,
+};
+
+/// Why a shard was not passed to the decoder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rejection {
+    /// The index is not lower than `original_count + recovery_count`.
+    OutOfRange,
+    /// A shard with the same index was already accepted.
+    Duplicate,
+    /// The shard is not `shard_bytes` long.
+    WrongLength,
+}
+
+impl Rejection {
+    fn reason(self) -> &'static str {
+        match self {
+            Rejection::OutOfRange => "out of range",
+            Rejection::Duplicate => "duplicate",
+            Rejection::WrongLength => "wrong length",
+        }
+    }
+}
+
+/// Shards accepted or rejected by the decoder (by their absolute indices).
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Ingestion {
+    originals: Vec<u16>,
+    recoveries: Vec<u16>,
+    rejected: Vec<(u16, Rejection)>,
+}
+
+/// Result of [`decode_verbose`].
+///
+/// NOTE the object holds WASM memory, so make sure to call `free()`
+/// on the JS side once it's not needed anymore.
+#[wasm_bindgen]
+pub struct DecodeReport {
+    ingestion: Ingestion,
+    shards: Option<ShardsCollection>,
+    error: Option<RsError>,
+}
+
+#[wasm_bindgen]
+impl DecodeReport {
+    /// Indices of the original shards passed to the decoder (in input order).
+    #[wasm_bindgen(getter)]
+    pub fn accepted_original_indices(&self) -> js_sys::Uint16Array {
+        self.ingestion.originals.as_slice().into()
+    }
+
+    /// Absolute indices of the recovery shards passed to the decoder (in input order).
+    #[wasm_bindgen(getter)]
+    pub fn accepted_recovery_indices(&self) -> js_sys::Uint16Array {
+        self.ingestion.recoveries.as_slice().into()
+    }
+
+    /// Indices of the shards which were rejected (in input order).
+    #[wasm_bindgen(getter)]
+    pub fn rejected_indices(&self) -> js_sys::Uint16Array {
+        let indices: Vec<u16> = self
+            .ingestion
+            .rejected
+            .iter()
+            .map(|(idx, _)| *idx)
+            .collect();
+        indices.as_slice().into()
+    }
+
+    /// Reasons (`"out of range"`, `"duplicate"` or `"wrong length"`)
+    /// matching `rejected_indices`.
+    #[wasm_bindgen(getter)]
+    pub fn rejection_reasons(&self) -> js_sys::Array {
+        self.ingestion
+            .rejected
+            .iter()
+            .map(|(_, rejection)| JsValue::from_str(rejection.reason()))
+            .collect()
+    }
+
+    /// Extract the restored original shards (as returned by `decode`), if decoding succeeded.
+    ///
+    /// NOTE that subsequent calls to that method will return `None`.
+    #[wasm_bindgen]
+    pub fn take_shards(&mut self) -> Option<ShardsCollection> {
+        self.shards.take()
+    }
+
+    /// The reason of the decoding failure (if it failed).
+    #[wasm_bindgen(getter)]
+    pub fn error(&self) -> Option<RsError> {
+        self.error.clone()
+    }
+}
+
+/// Same as [`crate::decode`], but reports which shards were used.
+///
+/// Instead of failing on the first invalid shard, such shards are skipped
+/// and listed in the report together with the reason. Decoding is then attempted
+/// with the accepted shards and its failure is recorded in the report as well.
+/// Only invalid parameters (e.g. `shard_bytes`) are thrown.
+#[wasm_bindgen]
+pub fn decode_verbose(
+    original_count: u16,
+    recovery_count: u16,
+    shard_bytes: u32,
+    shards: ShardsCollection,
+) -> Result<DecodeReport, RsError> {
+    let (ingestion, result) = rs_decode_verbose(
+        original_count as usize,
+        recovery_count as usize,
+        shard_bytes as usize,
+        shards,
+    )?;
+
+    let (shards, error) = match result {
+        Ok(shards) => (Some(shards.into()), None),
+        Err(e) => (None, Some(e)),
+    };
+    Ok(DecodeReport {
+        ingestion,
+        shards,
+        error,
+    })
+}
+
+fn rs_decode_verbose(
+    original_count: usize,
+    recovery_count: usize,
+    shard_bytes: usize,
+    shards: impl Shards,
+) -> Result<(Ingestion, Result<RsShardsCollection, RsError>), RsError> {
+    check_count("original_count", original_count)?;
+    validate_shard_bytes(shard_bytes)?;
+
+    let mut decoder = ReedSolomonDecoder::new(original_count, recovery_count, shard_bytes)?;
+    let mut ingestion = Ingestion::default();
+    let mut seen = vec![false; original_count + recovery_count];
+    let mut scratch = vec![];
+    for i in 0..shards.count() {
+        let idx = shards.index_at(i);
+        let rejection = match seen.get(idx as usize) {
+            _ if shards.shard_len() as usize != shard_bytes => Some(Rejection::WrongLength),
+            None => Some(Rejection::OutOfRange),
+            Some(true) => Some(Rejection::Duplicate),
+            Some(false) => None,
+        };
+        if let Some(rejection) = rejection {
+            ingestion.rejected.push((idx, rejection));
+            continue;
+        }
+        seen[idx as usize] = true;
+
+        let data = shards.read_chunk(i, &mut scratch);
+        if (idx as usize) < original_count {
+            decoder.add_original_shard(idx as usize, data)?;
+            ingestion.originals.push(idx);
+        } else {
+            decoder.add_recovery_shard(idx as usize - original_count, data)?;
+            ingestion.recoveries.push(idx);
+        }
+    }
+
+    let result = decoder
+        .decode()
+        .map(|result| restored_collection(shards.shard_len(), result.restored_original_iter()))
+        .map_err(RsError::from);
+
+    Ok((ingestion, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rs_encode, RsErrorCode};
+
+    const SHARD: usize = 64;
+
+    fn originals() -> RsShardsCollection {
+        RsShardsCollection {
+            length: 3,
+            shard_len: SHARD as u32,
+            data: (0..3 * SHARD).map(|i| (i / SHARD) as u8 + 1).collect(),
+            indices: None,
+        }
+    }
+
+    #[test]
+    fn should_categorize_accepted_and_rejected_shards() {
+        let recovery = rs_encode(2, SHARD, originals()).unwrap();
+        let shards = RsShardsCollection {
+            length: 5,
+            shard_len: SHARD as u32,
+            data: [
+                recovery.chunk_at(1),
+                &[2u8; SHARD][..],
+                &[0u8; SHARD][..],
+                recovery.chunk_at(1),
+                recovery.chunk_at(0),
+            ]
+            .concat(),
+            indices: Some(vec![4, 1, 9, 4, 3]),
+        };
+
+        let (ingestion, result) = rs_decode_verbose(3, 2, SHARD, shards).unwrap();
+
+        assert_eq!(
+            ingestion,
+            Ingestion {
+                originals: vec![1],
+                recoveries: vec![4, 3],
+                rejected: vec![(9, Rejection::OutOfRange), (4, Rejection::Duplicate)],
+            }
+        );
+        let decoded = result.unwrap();
+        assert_eq!(decoded.indices, Some(vec![0, 2]));
+        assert_eq!(decoded.chunk_at(0), &[1u8; SHARD]);
+        assert_eq!(decoded.chunk_at(1), &[3u8; SHARD]);
+    }
+
+    #[test]
+    fn should_report_decoding_failure() {
+        let shards = RsShardsCollection {
+            length: 2,
+            shard_len: SHARD as u32 * 2,
+            data: vec![0u8; 4 * SHARD],
+            indices: Some(vec![0, 1]),
+        };
+
+        let (ingestion, result) = rs_decode_verbose(3, 2, SHARD, shards).unwrap();
+
+        assert_eq!(
+            ingestion.rejected,
+            vec![(0, Rejection::WrongLength), (1, Rejection::WrongLength)]
+        );
+        assert!(ingestion.originals.is_empty());
+        assert_eq!(result.unwrap_err().code(), RsErrorCode::NotEnoughShards);
+    }
+}